@@ -3,15 +3,20 @@
 //! In our terminology, an `Action` is anything carried out on behalf of the players, for example
 //! playing a card. Drawing a card is not considered an `Action` - instead, that is viewed as if the
 //! player is being dealt a card by the game. Beginning a new game is also an `Action`.
+//!
+//! Each card in the rulesets has its own `PlayCardDetails` variant, holding exactly the targets
+//! and guess it takes. Cards from custom decks share the single `PlayCustom` variant, which holds
+//! at most one target.
 
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
 use crate::card::Card;
+use crate::choice::Choice;
 
 /// An external action that can be taken to progress a game of Love Letter.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub enum Action {
 
     /// A new game is beginning.
@@ -19,11 +24,51 @@ pub enum Action {
 
     /// One of the players plays a card.
     PlayCard { player_idx: usize, details: PlayCardDetails },
+
+    /// One of the players makes the choice that the card being played is waiting for.
+    MakeChoice { player_idx: usize, choice: Choice },
+
+    /// One of the players forfeits the game, and is eliminated. This may be done at any time, not
+    /// only on the player's turn.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::Action;
+    /// # use love_letter::builder::GameBuilder;
+    /// # use love_letter::card::Card;
+    /// # use love_letter::event::Event;
+    /// # use love_letter::ruleset::Ruleset;
+    /// use Card::*;
+    ///
+    /// let mut game = GameBuilder::new(3)
+    ///     .ruleset(Ruleset::Premium)
+    ///     .hand(0, &[Princess, Jester])
+    ///     .hand(1, &[Guard])
+    ///     .hand(2, &[Guard])
+    ///     .burned_card(Some(Guard))
+    ///     .deck(&[Assassin, Guard, Guard, Guard, Guard, Guard, Priest, Priest, Cardinal, Cardinal,
+    ///         Baron, Baron, Baroness, Baroness, Handmaid, Handmaid, Sycophant, Sycophant, Prince,
+    ///         Prince, Count, Count, King, Constable, Countess, DowagerQueen, Bishop])
+    ///     .build()
+    ///     .unwrap();
+    /// game.perform_action(&Action::Forfeit { player_idx: 1 }).unwrap();
+    /// let events = game.perform_action(&Action::Forfeit { player_idx: 2 }).unwrap();
+    ///
+    /// // Player 0's turn is cut short with two cards in hand, of which the Princess counts.
+    /// assert!(events.contains(&Event::FinalHand { player_idx: 0, card: Princess, score: 8, discard_total: 0 }));
+    /// assert_eq!(events.last(), Some(&Event::GameOver { winner_indices: vec![0] }));
+    /// ```
+    Forfeit { player_idx: usize },
+
+    /// The player due to play a card or make a choice has run out of time to do so, and is
+    /// eliminated.
+    Timeout { player_idx: usize },
 }
 
 /// Details about a play taken by one of the players.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub enum PlayCardDetails {
 
     /// A Guard has been played, and a guess has been made about another player's card.
@@ -49,10 +94,164 @@ pub enum PlayCardDetails {
 
     /// A Princess has been played.
     PlayPrincess {},
+
+    /// A Jester has been played on another player, who the player expects to win the round.
+    PlayJester { target_idx: Option<usize> },
+
+    /// An Assassin has been played.
+    PlayAssassin {},
+
+    /// A Cardinal has been played, making two players swap hands. The player then looks at the
+    /// hand now held by one of them, which is the first unless the game asks them to choose.
+    PlayCardinal { target_idx: Option<usize>, second_target_idx: Option<usize> },
+
+    /// A Baroness has been played on one or two other players.
+    PlayBaroness { target_idx: Option<usize>, second_target_idx: Option<usize> },
+
+    /// A Sycophant has been played on one of the players in the game.
+    PlaySycophant { target_idx: Option<usize> },
+
+    /// A Count has been played.
+    PlayCount {},
+
+    /// A Constable has been played.
+    PlayConstable {},
+
+    /// A Dowager Queen has been played on another player.
+    PlayDowagerQueen { target_idx: Option<usize> },
+
+    /// A Bishop has been played, and a guess has been made about another player's card.
+    PlayBishop { target_idx: Option<usize>, guess: Card },
+
+    /// The Deep Ones have been played, and a guess has been made about another player's card.
+    PlayDeepOnes { target_idx: Option<usize>, guess: Card },
+
+    /// The Golden Mead has been played on another player.
+    PlayGoldenMead { target_idx: Option<usize> },
+
+    /// The Hound of Tindalos has been played on another player.
+    PlayHoundOfTindalos { target_idx: Option<usize> },
+
+    /// The Liber Ivonis has been played.
+    PlayLiberIvonis {},
+
+    /// The Star Spawn of Cthulhu has been played on one of the players in the game.
+    PlayStarSpawn { target_idx: usize },
+
+    /// Nyarlathotep has been played on another player.
+    PlayNyarlathotep { target_idx: Option<usize> },
+
+    /// The Mi-Go has been played.
+    PlayMiGo {},
+
+    /// Cthulhu has been played.
+    PlayCthulhu {},
+
+    /// A card from a custom deck has been played, with a target and a guess if its effect calls
+    /// for them.
+    PlayCustom { id: u8, target_idx: Option<usize>, guess: Option<Card> },
 }
 
 impl PlayCardDetails {
 
+    /// Build the details of a play from the card being played, its target and, for the Guard, the
+    /// guess being made. Returns `None` if the parts do not describe a play of the given card.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::PlayCardDetails;
+    /// # use love_letter::card::Card;
+    /// let details = PlayCardDetails::from_parts(Card::Guard, Some(1), Some(Card::Baron));
+    /// assert_eq!(details, Some(PlayCardDetails::PlayGuard { target_idx: Some(1), guess: Card::Baron }));
+    /// assert_eq!(PlayCardDetails::from_parts(Card::Prince, None, None), None);
+    /// ```
+    pub fn from_parts(card: Card, target_idx: Option<usize>, guess: Option<Card>) -> Option<Self> {
+        Self::from_targets(card, target_idx.as_ref().map_or(&[], std::slice::from_ref), guess)
+    }
+
+    /// Build the details of a play from the card being played, its targets and, for the Guard,
+    /// Bishop, Deep Ones and custom cards, the guess being made. Returns `None` if the parts do not
+    /// describe a play of the given card.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::PlayCardDetails;
+    /// # use love_letter::card::Card;
+    /// let details = PlayCardDetails::from_targets(Card::Cardinal, &[2, 0], None);
+    /// assert_eq!(details, Some(PlayCardDetails::PlayCardinal { target_idx: Some(2), second_target_idx: Some(0) }));
+    /// assert_eq!(PlayCardDetails::from_targets(Card::Cardinal, &[2], None), None);
+    /// assert_eq!(PlayCardDetails::from_targets(Card::King, &[2, 0], None), None);
+    ///
+    /// let details = PlayCardDetails::from_targets(Card::Custom(1), &[2], None);
+    /// assert_eq!(details, Some(PlayCardDetails::PlayCustom { id: 1, target_idx: Some(2), guess: None }));
+    /// ```
+    pub fn from_targets(card: Card, targets: &[usize], guess: Option<Card>) -> Option<Self> {
+        let (target_idx, second_target_idx) = match *targets {
+            [] => (None, None),
+            [target_idx] => (Some(target_idx), None),
+            [target_idx, second_target_idx] => (Some(target_idx), Some(second_target_idx)),
+            _ => return None,
+        };
+        match (card, target_idx, second_target_idx, guess) {
+            (Card::Cardinal, Some(_), None, _) => None,
+            (Card::Cardinal, _, _, None) => Some(Self::PlayCardinal { target_idx, second_target_idx }),
+            (Card::Baroness, _, _, None) => Some(Self::PlayBaroness { target_idx, second_target_idx }),
+            (_, _, Some(_), _) => None,
+            (Card::Custom(id), _, _, _) => Some(Self::PlayCustom { id, target_idx, guess }),
+            (Card::Guard, _, _, Some(guess)) => Some(Self::PlayGuard { target_idx, guess }),
+            (Card::Guard, None, _, None) => Some(Self::PlayGuard { target_idx, guess: Card::Guard }),
+            (Card::Bishop, _, _, Some(guess)) => Some(Self::PlayBishop { target_idx, guess }),
+            (Card::Bishop, None, _, None) => Some(Self::PlayBishop { target_idx, guess: Card::Guard }),
+            (Card::DeepOnes, _, _, Some(guess)) => Some(Self::PlayDeepOnes { target_idx, guess }),
+            (Card::DeepOnes, None, _, None) => Some(Self::PlayDeepOnes { target_idx, guess: Card::Guard }),
+            (_, _, _, Some(_)) => None,
+            (Card::Priest, _, _, None) => Some(Self::PlayPriest { target_idx }),
+            (Card::Baron, _, _, None) => Some(Self::PlayBaron { target_idx }),
+            (Card::Handmaid, None, _, None) => Some(Self::PlayHandmaid {}),
+            (Card::Prince, Some(target_idx), _, None) => Some(Self::PlayPrince { target_idx }),
+            (Card::King, _, _, None) => Some(Self::PlayKing { target_idx }),
+            (Card::Countess, None, _, None) => Some(Self::PlayCountess {}),
+            (Card::Princess, None, _, None) => Some(Self::PlayPrincess {}),
+            (Card::Jester, _, _, None) => Some(Self::PlayJester { target_idx }),
+            (Card::Assassin, None, _, None) => Some(Self::PlayAssassin {}),
+            (Card::Sycophant, _, _, None) => Some(Self::PlaySycophant { target_idx }),
+            (Card::Count, None, _, None) => Some(Self::PlayCount {}),
+            (Card::Constable, None, _, None) => Some(Self::PlayConstable {}),
+            (Card::DowagerQueen, _, _, None) => Some(Self::PlayDowagerQueen { target_idx }),
+            (Card::GoldenMead, _, _, None) => Some(Self::PlayGoldenMead { target_idx }),
+            (Card::HoundOfTindalos, _, _, None) => Some(Self::PlayHoundOfTindalos { target_idx }),
+            (Card::LiberIvonis, None, _, None) => Some(Self::PlayLiberIvonis {}),
+            (Card::StarSpawn, Some(target_idx), _, None) => Some(Self::PlayStarSpawn { target_idx }),
+            (Card::Nyarlathotep, _, _, None) => Some(Self::PlayNyarlathotep { target_idx }),
+            (Card::MiGo, None, _, None) => Some(Self::PlayMiGo {}),
+            (Card::Cthulhu, None, _, None) => Some(Self::PlayCthulhu {}),
+            _ => None,
+        }
+    }
+
+    /// Get the guess associated with this play, if it is a Guard, a Bishop, the Deep Ones or a
+    /// custom card played with a guess.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::PlayCardDetails;
+    /// # use love_letter::card::Card;
+    /// let details = PlayCardDetails::PlayGuard { target_idx: Some(1), guess: Card::King };
+    /// assert_eq!(details.guess(), Some(Card::King));
+    /// ```
+    pub fn guess(&self) -> Option<Card> {
+        match self {
+            Self::PlayGuard { guess, .. } => Some(*guess),
+            Self::PlayBishop { guess, .. } => Some(*guess),
+            Self::PlayDeepOnes { guess, .. } => Some(*guess),
+            Self::PlayCustom { guess, .. } => *guess,
+            _ => None,
+        }
+    }
+
     /// Get the card associated with this play.
     ///
     /// # Examples
@@ -73,10 +272,28 @@ impl PlayCardDetails {
             Self::PlayKing { .. } => Card::King,
             Self::PlayCountess { .. } => Card::Countess,
             Self::PlayPrincess { .. } => Card::Princess,
+            Self::PlayJester { .. } => Card::Jester,
+            Self::PlayAssassin { .. } => Card::Assassin,
+            Self::PlayCardinal { .. } => Card::Cardinal,
+            Self::PlayBaroness { .. } => Card::Baroness,
+            Self::PlaySycophant { .. } => Card::Sycophant,
+            Self::PlayCount { .. } => Card::Count,
+            Self::PlayConstable { .. } => Card::Constable,
+            Self::PlayDowagerQueen { .. } => Card::DowagerQueen,
+            Self::PlayBishop { .. } => Card::Bishop,
+            Self::PlayDeepOnes { .. } => Card::DeepOnes,
+            Self::PlayGoldenMead { .. } => Card::GoldenMead,
+            Self::PlayHoundOfTindalos { .. } => Card::HoundOfTindalos,
+            Self::PlayLiberIvonis { .. } => Card::LiberIvonis,
+            Self::PlayStarSpawn { .. } => Card::StarSpawn,
+            Self::PlayNyarlathotep { .. } => Card::Nyarlathotep,
+            Self::PlayMiGo { .. } => Card::MiGo,
+            Self::PlayCthulhu { .. } => Card::Cthulhu,
+            Self::PlayCustom { id, .. } => Card::Custom(*id),
         }
     }
 
-    /// Get the target associated with this play.
+    /// Get the target associated with this play, or the first target if it has two.
     ///
     /// # Examples
     ///
@@ -95,7 +312,38 @@ impl PlayCardDetails {
             Self::PlayBaron { target_idx } => *target_idx,
             Self::PlayPrince { target_idx } => Some(*target_idx),
             Self::PlayKing { target_idx } => *target_idx,
+            Self::PlayJester { target_idx } => *target_idx,
+            Self::PlayCardinal { target_idx, .. } => *target_idx,
+            Self::PlayBaroness { target_idx, .. } => *target_idx,
+            Self::PlaySycophant { target_idx } => *target_idx,
+            Self::PlayDowagerQueen { target_idx } => *target_idx,
+            Self::PlayBishop { target_idx, .. } => *target_idx,
+            Self::PlayDeepOnes { target_idx, .. } => *target_idx,
+            Self::PlayGoldenMead { target_idx } => *target_idx,
+            Self::PlayHoundOfTindalos { target_idx } => *target_idx,
+            Self::PlayStarSpawn { target_idx } => Some(*target_idx),
+            Self::PlayNyarlathotep { target_idx } => *target_idx,
+            Self::PlayCustom { target_idx, .. } => *target_idx,
             _ => None,
         }
     }
+
+    /// Get every target associated with this play, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::PlayCardDetails;
+    /// let details = PlayCardDetails::PlayBaroness { target_idx: Some(3), second_target_idx: Some(1) };
+    /// assert_eq!(details.targets(), vec![3, 1]);
+    /// assert_eq!(PlayCardDetails::PlayPrince { target_idx: 0 }.targets(), vec![0]);
+    /// ```
+    pub fn targets(&self) -> Vec<usize> {
+        let second_target_idx = match self {
+            Self::PlayCardinal { second_target_idx, .. } => *second_target_idx,
+            Self::PlayBaroness { second_target_idx, .. } => *second_target_idx,
+            _ => None,
+        };
+        self.target().into_iter().chain(second_target_idx).collect()
+    }
 }
@@ -0,0 +1,308 @@
+//! Automated players for games of Love Letter.
+//!
+//! An `Agent` is anything capable of choosing a play on behalf of one of the seats in a game. It
+//! is shown an `Observation` of the game from its own seat along with the list of legal plays,
+//! and is informed of every event that it is entitled to see. In a game which asks players to make
+//! their own choices, it is also asked to make them.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::action::{Action, PlayCardDetails};
+use crate::choice::Choice;
+use crate::event::Event;
+use crate::game::{Game, GameError};
+use crate::observation::Observation;
+use crate::protocol::{self, SubprocessAgent};
+
+/// An automated player in a game of Love Letter.
+pub trait Agent {
+
+    /// A name for this agent, used when reporting results.
+    fn name(&self) -> &str;
+
+    /// Inform the agent that a new game is beginning, in which it occupies the given seat.
+    fn new_game(&mut self, _players: usize, _seat: usize) {}
+
+    /// Inform the agent of an event that it is entitled to see.
+    fn notify(&mut self, _event: &Event) {}
+
+    /// Choose one of the given legal plays. The list of legal plays is never empty.
+    fn choose_play(&mut self, observation: &Observation, legal_plays: &[PlayCardDetails]) -> PlayCardDetails;
+
+    /// Make one of the given choices, part of the way through the effect of a card. The list of
+    /// choices is never empty, and starts with the choice the game would otherwise have made.
+    fn make_choice(&mut self, _observation: &Observation, legal_choices: &[Choice]) -> Choice {
+        legal_choices[0]
+    }
+
+    /// Check whether the agent is still able to play, which an agent running elsewhere, such as a
+    /// bot in another process, may not be.
+    fn is_connected(&mut self) -> bool {
+        true
+    }
+
+    /// Check whether the agent ran out of time when it was last asked for a play or a choice, in
+    /// which case the one it returned was made on its behalf.
+    fn timed_out(&mut self) -> bool {
+        false
+    }
+}
+
+/// Settings for a game played between agents with `play_game_with`.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use love_letter::agent::{self, Agent, PlayOptions, RandomAgent};
+/// # use love_letter::event::{EliminationReason, Event};
+/// # use love_letter::game::Game;
+/// # use love_letter::protocol::SubprocessAgent;
+/// // A bot which introduces itself, but then hangs instead of making a play. However long the
+/// // handshake takes, its first move is certain to time out.
+/// let script = "read -r line; echo ok; while read -r line; do :; done";
+/// let bot = SubprocessAgent::spawn("sh", &["-c", script], Duration::from_secs(2)).unwrap();
+/// let mut agents: Vec<Box<dyn Agent>> = vec![Box::new(bot), Box::new(RandomAgent::new(0))];
+///
+/// let options = PlayOptions { eliminate_on_timeout: true, ..Default::default() };
+/// let events = agent::play_game_with(&mut Game::with_seed(0), &mut agents, options).unwrap();
+/// assert!(events.contains(&Event::EliminatePlayer { player_idx: 0, reason: EliminationReason::Timeout }));
+/// assert_eq!(events.last(), Some(&Event::GameOver { winner_indices: vec![1] }));
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct PlayOptions {
+
+    /// Whether a player whose agent is no longer connected forfeits the game, rather than having
+    /// their plays made on their behalf.
+    pub forfeit_on_disconnect: bool,
+
+    /// Whether a player whose agent runs out of time to make a play or a choice is eliminated,
+    /// rather than having it made on their behalf.
+    pub eliminate_on_timeout: bool,
+}
+
+/// An error preventing games between agents from being played.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum AgentError {
+
+    /// The named agent could not be created, for the given reason - for example, an external bot
+    /// which failed to start.
+    CreationFailed { name: String, reason: String },
+
+    /// The game engine rejected an action.
+    Game(GameError),
+}
+
+impl fmt::Display for AgentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use AgentError::*;
+        match self {
+            CreationFailed { name, reason } => write!(f, "Could not start {}: {}.", name, reason),
+            Game(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for AgentError {}
+
+impl From<GameError> for AgentError {
+    fn from(err: GameError) -> Self {
+        Self::Game(err)
+    }
+}
+
+/// A function creating a fresh agent from a seed, used to give each game its own agents.
+pub type AgentFactory = Arc<dyn Fn(u64) -> Result<Box<dyn Agent>, AgentError> + Send + Sync>;
+
+/// Get a factory for one of the built-in agents by name, or `None` if there is no such agent.
+///
+/// A name of the form `exec:<command>` refers to an external bot, started by running the given
+/// command (split on whitespace into a program and its arguments) and spoken to using the protocol
+/// described in the `protocol` module. The factory returns an error if the bot cannot be started.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::agent::{self, AgentError};
+/// let factory = agent::factory_by_name("random").unwrap();
+/// assert_eq!(factory(0).unwrap().name(), "random");
+/// assert!(agent::factory_by_name("nonsense").is_none());
+///
+/// let missing = agent::factory_by_name("exec:./no-such-bot").unwrap();
+/// assert!(matches!(missing(0), Err(AgentError::CreationFailed { .. })));
+/// ```
+pub fn factory_by_name(name: &str) -> Option<AgentFactory> {
+    match name {
+        "random" => Some(Arc::new(|seed| Ok(Box::new(RandomAgent::new(seed)) as Box<dyn Agent>))),
+        "lowest-card" => Some(Arc::new(|seed| Ok(Box::new(LowestCardAgent::new(seed)) as Box<dyn Agent>))),
+        _ => {
+            let command = name.strip_prefix("exec:")?.split_whitespace().map(String::from).collect::<Vec<_>>();
+            let (program, args) = command.split_first()?;
+            let (program, args) = (program.clone(), args.to_vec());
+            Some(Arc::new(move |_| {
+                let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+                match SubprocessAgent::spawn(&program, &args, protocol::DEFAULT_TIMEOUT) {
+                    Ok(agent) => Ok(Box::new(agent) as Box<dyn Agent>),
+                    Err(err) => Err(AgentError::CreationFailed { name: program.clone(), reason: err.to_string() }),
+                }
+            }))
+        }
+    }
+}
+
+/// An agent which chooses uniformly at random between the legal plays available to it.
+#[derive(Clone, Debug)]
+pub struct RandomAgent {
+    rng: StdRng,
+}
+
+impl RandomAgent {
+
+    /// Create a new `RandomAgent` whose choices are determined by the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Agent for RandomAgent {
+
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn choose_play(&mut self, _observation: &Observation, legal_plays: &[PlayCardDetails]) -> PlayCardDetails {
+        legal_plays.choose(&mut self.rng).unwrap().clone()
+    }
+}
+
+/// An agent which always plays its lowest-valued card, choosing targets and guesses at random.
+#[derive(Clone, Debug)]
+pub struct LowestCardAgent {
+    rng: StdRng,
+}
+
+impl LowestCardAgent {
+
+    /// Create a new `LowestCardAgent` whose choices are determined by the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Agent for LowestCardAgent {
+
+    fn name(&self) -> &str {
+        "lowest-card"
+    }
+
+    fn choose_play(&mut self, _observation: &Observation, legal_plays: &[PlayCardDetails]) -> PlayCardDetails {
+        let lowest = legal_plays.iter().map(|details| details.card()).min().unwrap();
+        let candidates = legal_plays.iter().filter(|details| details.card() == lowest).collect::<Vec<_>>();
+        candidates.choose(&mut self.rng).map(|&details| details.clone()).unwrap()
+    }
+}
+
+/// Play a complete game with one seat for each of the given agents, returning every event that
+/// occurred during the game. This uses the default `PlayOptions`.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::agent::{self, Agent, RandomAgent};
+/// # use love_letter::event::Event;
+/// # use love_letter::game::Game;
+/// let mut agents: Vec<Box<dyn Agent>> = vec![Box::new(RandomAgent::new(1)), Box::new(RandomAgent::new(2))];
+/// let events = agent::play_game(&mut Game::with_seed(3), &mut agents).unwrap();
+/// assert!(matches!(events.last(), Some(Event::GameOver { .. })));
+/// ```
+pub fn play_game(game: &mut Game, agents: &mut [Box<dyn Agent>]) -> Result<Vec<Event>, GameError> {
+    play_game_with(game, agents, PlayOptions::default())
+}
+
+/// Play a complete game with one seat for each of the given agents and the given options,
+/// returning every event that occurred during the game.
+///
+/// # Examples
+///
+/// ```
+/// # use std::thread;
+/// # use std::time::Duration;
+/// # use love_letter::agent::{self, Agent, PlayOptions, RandomAgent};
+/// # use love_letter::event::{EliminationReason, Event};
+/// # use love_letter::game::Game;
+/// # use love_letter::protocol::SubprocessAgent;
+/// // A bot which exits as soon as it has introduced itself.
+/// let mut bot = SubprocessAgent::spawn("sh", &["-c", "read -r line; echo ok"], Duration::from_secs(5)).unwrap();
+/// while bot.is_connected() {
+///     thread::sleep(Duration::from_millis(10));
+/// }
+/// let mut agents: Vec<Box<dyn Agent>> = vec![Box::new(RandomAgent::new(0)), Box::new(bot)];
+///
+/// let options = PlayOptions { forfeit_on_disconnect: true, ..Default::default() };
+/// let events = agent::play_game_with(&mut Game::with_seed(0), &mut agents, options).unwrap();
+/// assert!(events.contains(&Event::EliminatePlayer { player_idx: 1, reason: EliminationReason::Forfeit }));
+/// assert_eq!(events.last(), Some(&Event::GameOver { winner_indices: vec![0] }));
+/// ```
+pub fn play_game_with(game: &mut Game, agents: &mut [Box<dyn Agent>], options: PlayOptions) -> Result<Vec<Event>, GameError> {
+    let players = agents.len();
+    for (seat, agent) in agents.iter_mut().enumerate() {
+        agent.new_game(players, seat);
+    }
+
+    let mut history = Vec::new();
+    let mut events = game.perform_action(&Action::StartGame { players })?;
+
+    loop {
+        for event in &events {
+            for (seat, agent) in agents.iter_mut().enumerate() {
+                if event.is_visible_to(seat) {
+                    agent.notify(event);
+                }
+            }
+        }
+        history.append(&mut events);
+
+        if options.forfeit_on_disconnect {
+            let disconnected = (0..players).find(|&player_idx| {
+                game.validate(&Action::Forfeit { player_idx }).is_ok() && !agents[player_idx].is_connected()
+            });
+            if let Some(player_idx) = disconnected {
+                events = game.perform_action(&Action::Forfeit { player_idx })?;
+                continue;
+            }
+        }
+
+        if let Some(pending) = game.pending_choice() {
+            let player_idx = pending.player_idx;
+            let observation = game.observation(player_idx);
+            let legal_choices = game.legal_choices(player_idx);
+            let choice = agents[player_idx].make_choice(&observation, &legal_choices);
+            events = if options.eliminate_on_timeout && agents[player_idx].timed_out() {
+                game.perform_action(&Action::Timeout { player_idx })?
+            } else {
+                game.perform_action(&Action::MakeChoice { player_idx, choice })?
+            };
+            continue;
+        }
+
+        let player_idx = match game.current_player() {
+            Some(player_idx) => player_idx,
+            None => return Ok(history),
+        };
+
+        let observation = game.observation(player_idx);
+        let legal_plays = game.legal_plays(player_idx);
+        let details = agents[player_idx].choose_play(&observation, &legal_plays);
+        events = if options.eliminate_on_timeout && agents[player_idx].timed_out() {
+            game.perform_action(&Action::Timeout { player_idx })?
+        } else {
+            game.perform_action(&Action::PlayCard { player_idx, details })?
+        };
+    }
+}
@@ -0,0 +1,36 @@
+//! Play many games of arbitrary actions, checking that the engine never panics or hangs.
+//!
+//! Usage: `fuzz [--games N] [--seed N]`
+
+use std::env;
+use std::process;
+
+use love_letter::fuzz;
+
+fn usage() -> ! {
+    eprintln!("Usage: fuzz [--games N] [--seed N]");
+    process::exit(2);
+}
+
+fn main() {
+    let mut games = 10_000;
+    let mut seed = 0;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = || args.next().and_then(|n| n.parse().ok()).unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--games" => games = number() as usize,
+            "--seed" => seed = number(),
+            _ => usage(),
+        }
+    }
+
+    match fuzz::run(seed, games) {
+        Ok(()) => println!("{} games played without failure", games),
+        Err(failure) => {
+            print!("{}", failure);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Run every scenario in a directory and report which pass.
+//!
+//! Usage: `scenarios [DIR]`
+//!
+//! `DIR` defaults to `scenarios`. The exit status is non-zero if any scenario fails.
+
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+use love_letter::scenario;
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let dir = args.next().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("scenarios"));
+    if args.next().is_some() {
+        eprintln!("Usage: scenarios [DIR]");
+        process::exit(2);
+    }
+
+    let results = scenario::run_directory(&dir).unwrap_or_else(|err| {
+        eprintln!("{}: {}", dir.display(), err);
+        process::exit(2);
+    });
+
+    let mut failures = 0;
+    for outcome in &results {
+        match &outcome.result {
+            Ok(()) => println!("PASS {}", outcome.path.display()),
+            Err(err) => {
+                println!("FAIL {}: {}", outcome.path.display(), err);
+                failures += 1;
+            }
+        }
+    }
+
+    println!("{} passed, {} failed", results.len() - failures, failures);
+    if failures > 0 {
+        process::exit(1);
+    }
+}
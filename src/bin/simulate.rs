@@ -0,0 +1,52 @@
+//! Play many games between agents and report the results.
+//!
+//! Usage: `simulate [--games N] [--threads N] [--seed N] [--ruleset RULESET] [--rotate] [--csv] AGENT AGENT [AGENT...]`
+//!
+//! Each `AGENT` is one of `random`, `lowest-card` or `exec:<command>` for an external bot, and
+//! takes one seat at the table. `RULESET` is `classic` (the default), `premium` or `lovecraft`.
+
+use std::env;
+use std::process;
+
+use love_letter::agent;
+use love_letter::ruleset::Ruleset;
+use love_letter::simulation::{self, SimulationConfig};
+
+fn usage() -> ! {
+    eprintln!("Usage: simulate [--games N] [--threads N] [--seed N] [--ruleset RULESET] [--rotate] [--csv] AGENT AGENT [AGENT...]");
+    eprintln!("Available agents: random, lowest-card, exec:<command>");
+    process::exit(2);
+}
+
+fn main() {
+    let mut config = SimulationConfig::new(Ruleset::Classic, Vec::new(), 10_000);
+    config.threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut csv = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = || args.next().and_then(|n| n.parse().ok()).unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--games" => config.games = number() as usize,
+            "--threads" => config.threads = number() as usize,
+            "--seed" => config.seed = number(),
+            "--ruleset" => config.ruleset = args.next().and_then(|name| name.parse().ok()).unwrap_or_else(|| usage()),
+            "--rotate" => config.rotate_seats = true,
+            "--csv" => csv = true,
+            name => config.agents.push(agent::factory_by_name(name).unwrap_or_else(|| usage())),
+        }
+    }
+
+    if config.agents.len() < 2 {
+        usage();
+    }
+
+    match simulation::run(&config) {
+        Ok(stats) if csv => print!("{}", stats.to_csv()),
+        Ok(stats) => print!("{}", stats),
+        Err(err) => {
+            eprintln!("Simulation failed: {}", err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Run a tournament between agents and report the final standings.
+//!
+//! Usage: `tournament [--swiss ROUNDS] [--ruleset RULESET] [--table-size N] [--games N] [--seed N] AGENT AGENT [AGENT...]`
+//!
+//! Each `AGENT` is one of `random`, `lowest-card` or `exec:<command>` for an external bot, and
+//! may be given more than once to enter several copies of the same agent. Without `--swiss`, a
+//! round-robin tournament is played. `RULESET` is `classic` (the default), `premium` or
+//! `lovecraft`, which decides how large a table may be.
+
+use std::env;
+use std::process;
+
+use love_letter::agent;
+use love_letter::tournament::{self, Entrant, TournamentConfig, TournamentFormat};
+
+fn usage() -> ! {
+    eprintln!("Usage: tournament [--swiss ROUNDS] [--ruleset RULESET] [--table-size N] [--games N] [--seed N] AGENT AGENT [AGENT...]");
+    eprintln!("Available agents: random, lowest-card, exec:<command>");
+    process::exit(2);
+}
+
+fn main() {
+    let mut config = TournamentConfig::default();
+    let mut entrants = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = || args.next().and_then(|n| n.parse().ok()).unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--swiss" => config.format = TournamentFormat::Swiss { rounds: number() as usize },
+            "--ruleset" => config.ruleset = args.next().and_then(|name| name.parse().ok()).unwrap_or_else(|| usage()),
+            "--table-size" => config.table_size = number() as usize,
+            "--games" => config.games_per_match = number() as usize,
+            "--seed" => config.seed = number(),
+            name => entrants.push(Entrant {
+                name: format!("{}-{}", name, entrants.len()),
+                factory: agent::factory_by_name(name).unwrap_or_else(|| usage()),
+            }),
+        }
+    }
+
+    if entrants.len() < config.table_size {
+        usage();
+    }
+
+    match tournament::run(&entrants, &config) {
+        Ok(result) => print!("{}", result),
+        Err(err) => {
+            eprintln!("Tournament failed: {}", err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! Construction of games directly from a position in the middle of a round.
+//!
+//! Reaching a particular position by scripting a sequence of actions is tedious and fragile, since
+//! the position depends on the order in which the deck was shuffled. A `GameBuilder` instead sets
+//! out every player's hand and discards, the burned card, the order of the deck and whose turn it
+//! is, and checks that the result is a position that could arise in a real game.
+
+use std::error::Error;
+use std::fmt;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::card::Card;
+use crate::custom::{CustomDeck, DeckError};
+use crate::deck::Deck;
+use crate::game::Game;
+use crate::invariants::InvariantViolation;
+use crate::player::Player;
+use crate::ruleset::Ruleset;
+
+/// A reason why a `GameBuilder` does not describe a valid position.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum BuildError {
+
+    /// The game has an invalid number of players.
+    InvalidNumberOfPlayers(usize),
+
+    /// A player was referred to who isn't in the game.
+    PlayerDoesNotExist(usize),
+
+    /// The custom deck cannot be used.
+    InvalidDeck(DeckError),
+
+    /// The position is not one that could arise in a real game.
+    InvalidPosition(InvariantViolation),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use BuildError::*;
+        match self {
+            InvalidNumberOfPlayers(players) => write!(f, "Invalid number of players: {}. There must be between 2 and 4 players in a game, or up to 6 in Lovecraft Letter and 8 with the Premium cards.", players),
+            PlayerDoesNotExist(player_idx) => write!(f, "Player {} does not exist.", player_idx),
+            InvalidDeck(err) => write!(f, "Invalid deck: {}", err),
+            InvalidPosition(violation) => write!(f, "{}", violation),
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+/// A builder for a game in progress, starting from an arbitrary position.
+///
+/// The position must satisfy the same invariants as any game in progress, as described by
+/// `Game::check_invariants`. In particular, every card in the deck must be accounted for exactly
+/// once - in a hand, a discard pile, the deck, the burned card or among the cards removed from the
+/// game - and the player whose turn it is must hold two cards while every other player still in
+/// the game holds one. Nobody starts off knowing anything about anybody else's hand.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::action::{Action, PlayCardDetails};
+/// # use love_letter::builder::GameBuilder;
+/// # use love_letter::card::Card;
+/// use Card::*;
+///
+/// let mut game = GameBuilder::new(2)
+///     .hand(0, &[Guard, Baron])
+///     .hand(1, &[Princess])
+///     .discards(0, &[Guard, Priest])
+///     .discards(1, &[Guard, Handmaid])
+///     .removed_cards(&[Guard, Priest, Baron])
+///     .burned_card(Some(Handmaid))
+///     .deck(&[Prince, King, Countess, Guard, Prince])
+///     .current_player(0)
+///     .build()
+///     .unwrap();
+///
+/// // Player 0 can win on the spot with the right guess.
+/// let details = PlayCardDetails::PlayGuard { target_idx: Some(1), guess: Princess };
+/// game.perform_action(&Action::PlayCard { player_idx: 0, details }).unwrap();
+/// assert!(game.is_over());
+///
+/// // Losing a card is an error.
+/// let err = GameBuilder::new(2)
+///     .hand(0, &[Guard, Baron])
+///     .hand(1, &[Princess])
+///     .build()
+///     .unwrap_err();
+/// assert_eq!(err.to_string(), "There are 1 copies of the Guard but there should be 5.");
+/// ```
+#[derive(Clone, Debug)]
+pub struct GameBuilder {
+    ruleset: Ruleset,
+    custom_deck: Option<CustomDeck>,
+    hands: Vec<Vec<Card>>,
+    discards: Vec<Vec<Card>>,
+    protected: Vec<bool>,
+    eliminated: Vec<bool>,
+    deck: Vec<Card>,
+    burned_card: Option<Card>,
+    removed_cards: Vec<Card>,
+    current_player: usize,
+    seed: Option<u64>,
+    invalid_player: Option<usize>,
+}
+
+impl GameBuilder {
+
+    /// Begin building a classic game with the given number of players, in which nobody holds any
+    /// cards, the deck is empty and it is player 0's turn.
+    pub fn new(players: usize) -> Self {
+        Self {
+            ruleset: Ruleset::Classic,
+            custom_deck: None,
+            hands: vec![Vec::new(); players],
+            discards: vec![Vec::new(); players],
+            protected: vec![false; players],
+            eliminated: vec![false; players],
+            deck: Vec::new(),
+            burned_card: None,
+            removed_cards: Vec::new(),
+            current_player: 0,
+            seed: None,
+            invalid_player: None,
+        }
+    }
+
+    /// Set the rules under which the game is played, which determine the cards that must be
+    /// accounted for and the number of players allowed.
+    pub fn ruleset(mut self, ruleset: Ruleset) -> Self {
+        self.ruleset = ruleset;
+        self
+    }
+
+    /// Use a custom deck rather than the cards of the ruleset, which then determine the cards that
+    /// must be accounted for.
+    pub fn custom_deck(mut self, deck: CustomDeck) -> Self {
+        self.custom_deck = Some(deck);
+        self
+    }
+
+    /// Set the cards held by the given player, with the card they have held longest first.
+    pub fn hand(mut self, player_idx: usize, cards: &[Card]) -> Self {
+        if let Some(hand) = self.player_entry(player_idx, |builder| &mut builder.hands) {
+            *hand = cards.to_vec();
+        }
+        self
+    }
+
+    /// Set the cards discarded by the given player, in the order they were discarded.
+    pub fn discards(mut self, player_idx: usize, cards: &[Card]) -> Self {
+        if let Some(discards) = self.player_entry(player_idx, |builder| &mut builder.discards) {
+            *discards = cards.to_vec();
+        }
+        self
+    }
+
+    /// Mark the given player as protected by the Handmaid.
+    pub fn protected(mut self, player_idx: usize) -> Self {
+        if let Some(protected) = self.player_entry(player_idx, |builder| &mut builder.protected) {
+            *protected = true;
+        }
+        self
+    }
+
+    /// Mark the given player as having been eliminated.
+    pub fn eliminated(mut self, player_idx: usize) -> Self {
+        if let Some(eliminated) = self.player_entry(player_idx, |builder| &mut builder.eliminated) {
+            *eliminated = true;
+        }
+        self
+    }
+
+    /// Set the cards remaining in the deck, with the top of the deck (the next card to be drawn)
+    /// first.
+    pub fn deck(mut self, cards: &[Card]) -> Self {
+        self.deck = cards.iter().rev().copied().collect();
+        self
+    }
+
+    /// Set the burned card, or `None` if it has already been drawn.
+    pub fn burned_card(mut self, card: Option<Card>) -> Self {
+        self.burned_card = card;
+        self
+    }
+
+    /// Set the cards publicly removed from the game at the start of a two-player game.
+    pub fn removed_cards(mut self, cards: &[Card]) -> Self {
+        self.removed_cards = cards.to_vec();
+        self
+    }
+
+    /// Set the player whose turn it is.
+    pub fn current_player(mut self, player_idx: usize) -> Self {
+        if player_idx >= self.hands.len() {
+            self.invalid_player.get_or_insert(player_idx);
+        }
+        self.current_player = player_idx;
+        self
+    }
+
+    /// Seed the random number generator used to shuffle the deck for any later games.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Check that the position is valid, and create a game in that position.
+    pub fn build(&self) -> Result<Game, BuildError> {
+        let players = self.hands.len();
+        if !self.ruleset.players().contains(&players) {
+            return Err(BuildError::InvalidNumberOfPlayers(players));
+        }
+        if let Some(player_idx) = self.invalid_player {
+            return Err(BuildError::PlayerDoesNotExist(player_idx));
+        }
+        if let Some(deck) = &self.custom_deck {
+            deck.validate(players).map_err(BuildError::InvalidDeck)?;
+        }
+
+        let players = (0..players).map(|player_idx| {
+            let mut player = Player::new();
+            player.set_hand(self.hands[player_idx].clone());
+            player.set_discards(self.discards[player_idx].clone());
+            if self.protected[player_idx] {
+                player.make_protected();
+            }
+            if self.eliminated[player_idx] {
+                player.eliminate();
+            }
+            player
+        }).collect();
+
+        let rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        let mut game = Game::from_position(
+            self.ruleset,
+            players,
+            Deck::from_cards(self.deck.clone()),
+            self.burned_card,
+            self.removed_cards.clone(),
+            self.current_player,
+            rng,
+        );
+        if let Some(deck) = &self.custom_deck {
+            game = game.with_deck(deck.clone());
+        }
+        game.check_invariants().map_err(BuildError::InvalidPosition)?;
+        Ok(game)
+    }
+
+    /// Get the entry for the given player in one of the per-player lists, recording an error if
+    /// the player doesn't exist.
+    fn player_entry<T>(&mut self, player_idx: usize, list: impl FnOnce(&mut Self) -> &mut Vec<T>) -> Option<&mut T> {
+        if player_idx >= self.hands.len() {
+            self.invalid_player.get_or_insert(player_idx);
+            return None;
+        }
+        list(self).get_mut(player_idx)
+    }
+}
@@ -1,11 +1,31 @@
 //! Definitions of the cards contained in the game of Love Letter.
 
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
 #[cfg(feature = "serde")]
-use serde::{Serialize, Deserialize};
+use serde::de;
+
+use crate::effect::{self, EffectRegistry};
 
 /// A single card belonging to a Love Letter deck.
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+///
+/// Cards are ordered by value. Cards sharing a value are ordered with the card from the classic
+/// edition first, followed by the cards added by the Premium edition and then the insane cards of
+/// Lovecraft Letter, in the order listed here. Custom cards come after all of these, in order of
+/// their number.
+///
+/// With the `serde` feature, cards are serialized as their names, in the same way as they are
+/// parsed by `FromStr`.
+///
+/// The classic cards keep the discriminants 1 to 8, matching their values. Since `Custom` carries
+/// data, a `Card` cannot be cast with `as`; use `value` or `number` instead.
+#[repr(u8)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum Card {
 
     /// The Guard, with a value of 1
@@ -31,11 +51,180 @@ pub enum Card {
 
     /// The Princess, with a value of 8
     Princess = 8,
+
+    /// The Jester, with a value of 0, from the Premium edition
+    Jester,
+
+    /// The Assassin, with a value of 0, from the Premium edition
+    Assassin,
+
+    /// The Cardinal, with a value of 2, from the Premium edition
+    Cardinal,
+
+    /// The Baroness, with a value of 3, from the Premium edition
+    Baroness,
+
+    /// The Sycophant, with a value of 4, from the Premium edition
+    Sycophant,
+
+    /// The Count, with a value of 5, from the Premium edition
+    Count,
+
+    /// The Constable, with a value of 6, from the Premium edition
+    Constable,
+
+    /// The Dowager Queen, with a value of 7, from the Premium edition
+    DowagerQueen,
+
+    /// The Bishop, with a value of 9, from the Premium edition
+    Bishop,
+
+    /// The Deep Ones, an insane card with a value of 1, from Lovecraft Letter
+    DeepOnes,
+
+    /// The Golden Mead, an insane card with a value of 2, from Lovecraft Letter
+    GoldenMead,
+
+    /// The Hound of Tindalos, an insane card with a value of 3, from Lovecraft Letter
+    HoundOfTindalos,
+
+    /// The Liber Ivonis, an insane card with a value of 4, from Lovecraft Letter
+    LiberIvonis,
+
+    /// The Star Spawn of Cthulhu, an insane card with a value of 5, from Lovecraft Letter
+    StarSpawn,
+
+    /// Nyarlathotep, an insane card with a value of 6, from Lovecraft Letter
+    Nyarlathotep,
+
+    /// The Mi-Go, an insane card with a value of 7, from Lovecraft Letter
+    MiGo,
+
+    /// Cthulhu, an insane card with a value of 8, from Lovecraft Letter
+    Cthulhu,
+
+    /// A card from a custom deck, identified by its position in the deck's definition. Its name,
+    /// value and effect are given by that definition.
+    Custom(u8),
 }
 
 impl Card {
 
-    /// Returns whether or not this `Card` is one whose action has a target.
+    /// Every distinct card in the classic edition of the game, in ascending order.
+    pub const CLASSIC: [Card; 8] = [
+        Card::Guard, Card::Priest, Card::Baron, Card::Handmaid,
+        Card::Prince, Card::King, Card::Countess, Card::Princess,
+    ];
+
+    /// The insane cards of Lovecraft Letter, in ascending order. The sane cards of Lovecraft Letter
+    /// are the classic cards under other names.
+    pub const INSANE: [Card; 8] = [
+        Card::DeepOnes, Card::GoldenMead, Card::HoundOfTindalos, Card::LiberIvonis,
+        Card::StarSpawn, Card::Nyarlathotep, Card::MiGo, Card::Cthulhu,
+    ];
+
+    /// Every distinct card in any edition of the game, in ascending order.
+    pub const ALL: [Card; 25] = [
+        Card::Jester, Card::Assassin, Card::Guard, Card::DeepOnes, Card::Priest, Card::Cardinal,
+        Card::GoldenMead, Card::Baron, Card::Baroness, Card::HoundOfTindalos, Card::Handmaid,
+        Card::Sycophant, Card::LiberIvonis, Card::Prince, Card::Count, Card::StarSpawn, Card::King,
+        Card::Constable, Card::Nyarlathotep, Card::Countess, Card::DowagerQueen, Card::MiGo,
+        Card::Princess, Card::Cthulhu, Card::Bishop,
+    ];
+
+    /// Get the value of this `Card`, which is used to compare hands and to score the end of a
+    /// round. Guesses made with the Guard and Bishop are about values rather than cards. Custom
+    /// cards have no value of their own, and this returns zero for them; their value in a game is
+    /// given by their effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::card::Card;
+    /// assert_eq!(Card::Princess.value(), 8);
+    /// assert_eq!(Card::Cardinal.value(), Card::Priest.value());
+    /// ```
+    pub fn value(self) -> u32 {
+        match self {
+            Self::Jester | Self::Assassin => 0,
+            Self::Guard | Self::DeepOnes => 1,
+            Self::Priest | Self::Cardinal | Self::GoldenMead => 2,
+            Self::Baron | Self::Baroness | Self::HoundOfTindalos => 3,
+            Self::Handmaid | Self::Sycophant | Self::LiberIvonis => 4,
+            Self::Prince | Self::Count | Self::StarSpawn => 5,
+            Self::King | Self::Constable | Self::Nyarlathotep => 6,
+            Self::Countess | Self::DowagerQueen | Self::MiGo => 7,
+            Self::Princess | Self::Cthulhu => 8,
+            Self::Bishop => 9,
+            Self::Custom(_) => 0,
+        }
+    }
+
+    /// Get the number of this `Card`, which identifies it in hashes and compact representations.
+    /// The classic cards are numbered from 1 to 8, so that their number is also their value, and
+    /// are followed by the Premium cards, the insane cards and then the custom cards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::card::Card;
+    /// assert_eq!(Card::Baron.number(), 3);
+    /// assert_eq!(Card::Jester.number(), 9);
+    /// assert_eq!(Card::Cthulhu.number(), 25);
+    /// assert_eq!(Card::Custom(0).number(), 26);
+    /// ```
+    pub fn number(self) -> usize {
+        match self {
+            Self::Guard => 1,
+            Self::Priest => 2,
+            Self::Baron => 3,
+            Self::Handmaid => 4,
+            Self::Prince => 5,
+            Self::King => 6,
+            Self::Countess => 7,
+            Self::Princess => 8,
+            Self::Jester => 9,
+            Self::Assassin => 10,
+            Self::Cardinal => 11,
+            Self::Baroness => 12,
+            Self::Sycophant => 13,
+            Self::Count => 14,
+            Self::Constable => 15,
+            Self::DowagerQueen => 16,
+            Self::Bishop => 17,
+            Self::DeepOnes => 18,
+            Self::GoldenMead => 19,
+            Self::HoundOfTindalos => 20,
+            Self::LiberIvonis => 21,
+            Self::StarSpawn => 22,
+            Self::Nyarlathotep => 23,
+            Self::MiGo => 24,
+            Self::Cthulhu => 25,
+            Self::Custom(id) => Self::ALL.len() + 1 + id as usize,
+        }
+    }
+
+    /// Compare the strength of this card against another, as when two hands are compared. The
+    /// card with the higher value is stronger, except that the Princess beats the Bishop.
+    ///
+    /// This follows the standard rules only, and ignores any effects overridden with
+    /// `Game::with_effect`. Use `Game::effect` to find out how cards behave in a particular game.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::cmp::Ordering;
+    /// # use love_letter::card::Card;
+    /// assert_eq!(Card::King.strength_cmp(Card::Constable), Ordering::Equal);
+    /// assert_eq!(Card::Bishop.strength_cmp(Card::Countess), Ordering::Greater);
+    /// assert_eq!(Card::Bishop.strength_cmp(Card::Princess), Ordering::Less);
+    /// ```
+    pub fn strength_cmp(self, other: Card) -> Ordering {
+        EffectRegistry::new().strength_cmp(self, other)
+    }
+
+    /// Returns whether or not this `Card` is one whose action has a target, under the standard
+    /// rules only. See `strength_cmp`.
     ///
     /// # Examples
     ///
@@ -45,9 +234,104 @@ impl Card {
     /// assert!(!Card::Handmaid.has_target());
     /// ```
     pub fn has_target(self) -> bool {
-        match self {
-            Self::Guard | Self::Priest | Self::Baron | Self::Prince | Self::King => true,
-            _ => false,
-        }
+        effect::standard(self).targeting().has_target()
+    }
+
+    /// Returns whether or not this `Card` may be played with its player as a target, under the
+    /// standard rules only. See `strength_cmp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::card::Card;
+    /// assert!(Card::Prince.can_target_self());
+    /// assert!(!Card::King.can_target_self());
+    /// ```
+    pub fn can_target_self(self) -> bool {
+        effect::standard(self).targeting().include_self
+    }
+
+    /// Get the number of targets this `Card` must be given when enough players can be targeted,
+    /// under the standard rules only. See `strength_cmp`. The Baroness may optionally be given a
+    /// second target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::card::Card;
+    /// assert_eq!(Card::Handmaid.required_targets(), 0);
+    /// assert_eq!(Card::Baroness.required_targets(), 1);
+    /// assert_eq!(Card::Cardinal.required_targets(), 2);
+    /// ```
+    pub fn required_targets(self) -> usize {
+        effect::standard(self).targeting().required
+    }
+}
+
+impl Ord for Card {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |card: Card| (matches!(card, Card::Custom(_)), card.value(), card.number());
+        key(*self).cmp(&key(*other))
+    }
+}
+
+impl PartialOrd for Card {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
+
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    /// Parse a card from its name, ignoring case. Custom cards are written as they are printed by
+    /// their `Debug` implementation, for example `Custom(3)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::card::Card;
+    /// assert_eq!("baron".parse(), Ok(Card::Baron));
+    /// assert_eq!("dowagerqueen".parse(), Ok(Card::DowagerQueen));
+    /// assert_eq!("Custom(3)".parse(), Ok(Card::Custom(3)));
+    /// assert!("Joker".parse::<Card>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let custom = s.get(..7).filter(|prefix| prefix.eq_ignore_ascii_case("custom("))
+            .and_then(|_| s[7..].strip_suffix(')'))
+            .and_then(|id| id.parse().ok())
+            .map(Card::Custom);
+        Self::ALL.iter()
+            .find(|card| format!("{:?}", card).eq_ignore_ascii_case(s))
+            .copied()
+            .or(custom)
+            .ok_or_else(|| ParseCardError(s.to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Card {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{:?}", self))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(de::Error::custom)
+    }
+}
+
+/// An error returned when a string does not name a card.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ParseCardError(String);
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown card: {}.", self.0)
+    }
+}
+
+impl Error for ParseCardError {}
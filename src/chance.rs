@@ -0,0 +1,148 @@
+//! Exact enumeration of the chance outcomes of a play.
+//!
+//! The only randomness in a game of Love Letter comes from the order of the deck. A `Game` stores
+//! the deck in a fixed order, but for exact expectimax search and game-theoretic analysis it is
+//! more useful to treat the order of the deck as unknown: every ordering of the cards remaining in
+//! the deck is equally likely. Under this view, a play can lead to several distinct outcomes,
+//! depending on which cards are drawn as a result of it - for example the card drawn by the target
+//! of a Prince, and the card drawn by the next player at the start of their turn.
+//!
+//! The identity of the burned card is considered part of the state, as are the contents (but not
+//! the order) of the deck.
+//!
+//! In games where players make their own choices (see `Game::with_choices`), a play that calls for
+//! a choice stops before anything further is drawn, and the outcomes of the choice itself are
+//! enumerated in the same way.
+
+use crate::action::{Action, PlayCardDetails};
+use crate::card::Card;
+use crate::choice::Choice;
+use crate::event::Event;
+use crate::game::{Game, GameError};
+
+/// One of the possible outcomes of a play.
+#[derive(Clone, Debug)]
+pub struct ChanceOutcome {
+
+    /// The probability of this outcome.
+    pub probability: f64,
+
+    /// The events which occur in this outcome.
+    pub events: Vec<Event>,
+
+    /// The state of the game after this outcome. The order of the cards remaining in its deck is
+    /// arbitrary, and should itself be treated as unknown.
+    pub game: Game,
+}
+
+impl Game {
+
+    /// Enumerate every distinct outcome of the given player making the given play, treating the
+    /// order of the deck as unknown, along with the probability of each outcome. Outcomes are
+    /// distinguished by the sequence of cards drawn from the deck during the play.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::Action;
+    /// # use love_letter::game::Game;
+    /// let mut game = Game::new();
+    /// game.perform_action(&Action::StartGame { players: 3 }).unwrap();
+    ///
+    /// for details in game.legal_plays(0) {
+    ///     let outcomes = game.chance_outcomes(0, &details).unwrap();
+    ///     let total = outcomes.iter().map(|outcome| outcome.probability).sum::<f64>();
+    ///     assert!((total - 1.0).abs() < 1e-9);
+    /// }
+    /// ```
+    pub fn chance_outcomes(&self, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<ChanceOutcome>, GameError> {
+        self.action_outcomes(&Action::PlayCard { player_idx, details: details.clone() })
+    }
+
+    /// Enumerate every distinct outcome of the given player making the given choice, in the same
+    /// way as `chance_outcomes` does for a play.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::{Action, PlayCardDetails};
+    /// # use love_letter::builder::GameBuilder;
+    /// # use love_letter::card::Card;
+    /// # use love_letter::choice::Choice;
+    /// # use love_letter::ruleset::Ruleset;
+    /// use Card::*;
+    ///
+    /// let mut game = GameBuilder::new(3)
+    ///     .ruleset(Ruleset::Premium)
+    ///     .hand(0, &[Cardinal, Guard])
+    ///     .hand(1, &[King])
+    ///     .hand(2, &[Baron])
+    ///     .deck(&[Jester, Assassin, Guard, Guard, Guard, Guard, Guard, Guard, Guard, Priest, Priest, Cardinal,
+    ///         Baron, Baroness, Baroness, Handmaid, Handmaid, Sycophant, Sycophant, Prince, Prince, Count,
+    ///         Count, Constable, Countess, DowagerQueen, Princess, Bishop])
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_choices();
+    ///
+    /// // Playing the Cardinal stops to ask which hand to look at, before anybody draws a card.
+    /// let details = PlayCardDetails::PlayCardinal { target_idx: Some(1), second_target_idx: Some(2) };
+    /// assert_eq!(game.chance_outcomes(0, &details).unwrap().len(), 1);
+    /// game.perform_action(&Action::PlayCard { player_idx: 0, details }).unwrap();
+    ///
+    /// // Once the choice is made, the next player draws one of the 16 distinct cards in the deck.
+    /// let outcomes = game.choice_outcomes(0, Choice::LookAtHand { target_idx: 2 }).unwrap();
+    /// assert_eq!(outcomes.len(), 16);
+    /// let total = outcomes.iter().map(|outcome| outcome.probability).sum::<f64>();
+    /// assert!((total - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn choice_outcomes(&self, player_idx: usize, choice: Choice) -> Result<Vec<ChanceOutcome>, GameError> {
+        self.action_outcomes(&Action::MakeChoice { player_idx, choice })
+    }
+
+    /// Enumerate every distinct outcome of the given action, along with its probability.
+    fn action_outcomes(&self, action: &Action) -> Result<Vec<ChanceOutcome>, GameError> {
+
+        // Check that the action is legal before enumerating anything.
+        self.clone().perform_action(action)?;
+
+        let mut outcomes = Vec::new();
+        self.expand_outcomes(action, &mut Vec::new(), 1.0, &mut outcomes);
+        Ok(outcomes)
+    }
+
+    /// Enumerate the outcomes of the action in which the first cards drawn are those in `drawn`,
+    /// which together occur with the given probability.
+    fn expand_outcomes(&self, action: &Action, drawn: &mut Vec<Card>, probability: f64, outcomes: &mut Vec<ChanceOutcome>) {
+
+        // Arrange the deck so that the chosen cards are drawn first, in order.
+        let mut remaining = self.deck_cards().to_vec();
+        for card in drawn.iter() {
+            let position = remaining.iter().position(|c| c == card).unwrap();
+            remaining.remove(position);
+        }
+        let mut arranged = remaining.clone();
+        arranged.extend(drawn.iter().rev());
+
+        let mut game = self.clone();
+        game.set_deck_cards(arranged);
+        let events = game.perform_action(action).unwrap();
+
+        // If no more cards were drawn than we chose, this is a complete outcome. Otherwise, branch
+        // on each distinct card that could be drawn next.
+        let cards_drawn = self.deck_cards().len() - game.deck_cards().len();
+        if cards_drawn <= drawn.len() {
+            outcomes.push(ChanceOutcome { probability, events, game });
+            return;
+        }
+
+        let mut distinct = remaining.clone();
+        distinct.sort();
+        distinct.dedup();
+        for card in distinct {
+            let count = remaining.iter().filter(|&&c| c == card).count();
+            drawn.push(card);
+            self.expand_outcomes(action, drawn, probability * count as f64 / remaining.len() as f64, outcomes);
+            drawn.pop();
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Choices which a player must make part of the way through a card's effect.
+//!
+//! Most cards are carried out in full as soon as they are played, but a few call for a decision
+//! that can only be made once the card has partly taken effect. After making two players swap hands
+//! with the Cardinal, its player chooses which of their hands to look at. After another player has
+//! correctly guessed their card with the Bishop, the target chooses whether to discard it and draw
+//! a new one.
+//!
+//! By default, the engine makes these choices on the players' behalf, as described by
+//! `ChoiceKind::default_choice`, and every play is carried out in a single action. A game created
+//! with `Game::with_choices` instead stops when a choice is needed, announcing it with
+//! `Event::ChoiceRequired`, and waits for the player to answer with `Action::MakeChoice`. No card
+//! can be played until they have done so.
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::card::Card;
+
+/// A decision which a player must make before a card's effect can be finished.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum ChoiceKind {
+
+    /// Choose which of two players to look at, having made them swap hands with the Cardinal.
+    LookAtHand { first_idx: usize, second_idx: usize },
+
+    /// Choose whether to discard the card in hand and draw a new one, after it was guessed
+    /// correctly with the Bishop.
+    Redraw,
+}
+
+impl ChoiceKind {
+
+    /// Get every choice that may be made, starting with the default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::choice::{Choice, ChoiceKind};
+    /// let kind = ChoiceKind::LookAtHand { first_idx: 2, second_idx: 0 };
+    /// assert_eq!(kind.options(), vec![Choice::LookAtHand { target_idx: 2 }, Choice::LookAtHand { target_idx: 0 }]);
+    /// assert_eq!(ChoiceKind::Redraw.options().len(), 2);
+    /// ```
+    pub fn options(self) -> Vec<Choice> {
+        match self {
+            Self::LookAtHand { first_idx, second_idx } => vec![
+                Choice::LookAtHand { target_idx: first_idx },
+                Choice::LookAtHand { target_idx: second_idx },
+            ],
+            Self::Redraw => vec![Choice::Redraw { discard: false }, Choice::Redraw { discard: true }],
+        }
+    }
+
+    /// Get the choice made on the player's behalf when the game does not ask for it: looking at
+    /// the first of the Cardinal's targets, and keeping a card guessed with the Bishop.
+    pub fn default_choice(self) -> Choice {
+        self.options()[0]
+    }
+}
+
+/// An answer to a `ChoiceKind`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Choice {
+
+    /// Look at the given player's hand.
+    LookAtHand { target_idx: usize },
+
+    /// Discard the card in hand and draw a new one, or keep it.
+    Redraw { discard: bool },
+}
+
+/// A choice which has yet to be made.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct PendingChoice {
+
+    /// The card whose effect is waiting for the choice.
+    pub card: Card,
+
+    /// The player who must make the choice.
+    pub player_idx: usize,
+
+    /// The decision to be made.
+    pub kind: ChoiceKind,
+}
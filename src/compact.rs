@@ -0,0 +1,746 @@
+//! A compact representation of a game of Love Letter, for high-throughput search.
+//!
+//! A `CompactGame` follows exactly the same rules as a classic `Game` with the standard effect for
+//! every card, which it builds in rather than consulting an `EffectRegistry`. It stores its state
+//! in a handful of fixed-size integers, so that it is `Copy` and can be duplicated for free by
+//! search algorithms.
+//! The deck is packed four bits to a card into a single `u64`, each player's discards are stored
+//! as a count of each card packed three bits to a card into a `u32`, and the flags for each player
+//! are stored as bitsets.
+//!
+//! Copying a game never allocates, but not every operation is free of allocation: `legal_plays`
+//! returns a `Vec`, and the events which end a game carry the list of winners.
+//!
+//! Since a `CompactGame` cannot own a random number generator, one must be supplied when starting
+//! a game. Events are written to any `Extend<Event>` - use `IgnoreEvents` when they aren't needed.
+//! The `cross_check` function plays a sequence of actions through both engines and checks that they
+//! agree at every step.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::action::{Action, PlayCardDetails};
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::event::{EliminationReason, Event, WinReason};
+use crate::game::{Game, GameError};
+use crate::ruleset::Ruleset;
+
+/// The largest number of players supported by a `CompactGame`.
+pub const MAX_PLAYERS: usize = 4;
+
+/// A card encoded as its value, with 0 representing no card.
+type PackedCard = u8;
+
+/// A sink for events which discards them.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct IgnoreEvents;
+
+impl Extend<Event> for IgnoreEvents {
+    fn extend<T: IntoIterator<Item = Event>>(&mut self, _events: T) {}
+}
+
+/// The high-level state of a `CompactGame`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+enum CompactState {
+    NotStarted,
+    InProgress,
+    Complete,
+}
+
+/// A game of Love Letter whose whole state is held in fixed-size integers.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct CompactGame {
+
+    /// The cards in the deck, four bits per card, with the top card in the highest occupied bits.
+    deck: u64,
+
+    /// The number of cards in the deck.
+    deck_len: u8,
+
+    /// The burned card, or 0 if it has been drawn.
+    burned_card: PackedCard,
+
+    /// The cards in each player's hand, highest first, with 0 for an empty slot.
+    hands: [[PackedCard; 2]; MAX_PLAYERS],
+
+    /// The number of each card discarded by each player, three bits per card.
+    discards: [u32; MAX_PLAYERS],
+
+    /// The number of players in the game.
+    players: u8,
+
+    /// A bitset of the players still in the game.
+    active: u8,
+
+    /// A bitset of the players protected by the Handmaid.
+    protected: u8,
+
+    /// The player whose turn it is to play.
+    turn_counter: u8,
+
+    /// The high-level state of the game.
+    state: CompactState,
+}
+
+/// Convert a card to its packed representation.
+fn pack(card: Card) -> PackedCard {
+    card.number() as PackedCard
+}
+
+/// Convert a packed card back to a card, or `None` for an empty slot.
+fn unpack(card: PackedCard) -> Option<Card> {
+    if card == 0 { None } else { Some(Card::CLASSIC[card as usize - 1]) }
+}
+
+impl CompactGame {
+
+    /// Create a new game which has not yet started.
+    pub fn new() -> Self {
+        Self {
+            deck: 0,
+            deck_len: 0,
+            burned_card: 0,
+            hands: [[0; 2]; MAX_PLAYERS],
+            discards: [0; MAX_PLAYERS],
+            players: 0,
+            active: 0,
+            protected: 0,
+            turn_counter: 0,
+            state: CompactState::NotStarted,
+        }
+    }
+
+    /// Get the number of players in the current game.
+    pub fn num_players(&self) -> usize {
+        self.players as usize
+    }
+
+    /// Check whether the game has finished.
+    pub fn is_over(&self) -> bool {
+        self.state == CompactState::Complete
+    }
+
+    /// Get the player whose turn it is, or `None` if no game is in progress.
+    pub fn current_player(&self) -> Option<usize> {
+        match self.state {
+            CompactState::InProgress => Some(self.turn_counter as usize),
+            _ => None,
+        }
+    }
+
+    /// Carry out the given action, writing the resulting events to `events`. The random number
+    /// generator is used to shuffle the deck when starting a new game.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rand::SeedableRng;
+    /// # use rand::rngs::StdRng;
+    /// # use love_letter::action::Action;
+    /// # use love_letter::compact::{CompactGame, IgnoreEvents};
+    /// let mut game = CompactGame::new();
+    /// let mut rng = StdRng::seed_from_u64(0);
+    /// game.perform_action(&Action::StartGame { players: 4 }, &mut rng, &mut IgnoreEvents).unwrap();
+    ///
+    /// // Copying the game is free, so search algorithms can explore each play independently.
+    /// for details in game.legal_plays(0) {
+    ///     let mut copy = game;
+    ///     let mut events = Vec::new();
+    ///     copy.perform_action(&Action::PlayCard { player_idx: 0, details }, &mut rng, &mut events).unwrap();
+    ///     assert!(!events.is_empty());
+    /// }
+    /// ```
+    pub fn perform_action<R, E>(&mut self, action: &Action, rng: &mut R, events: &mut E) -> Result<(), GameError>
+        where R: Rng + ?Sized, E: Extend<Event>
+    {
+        match action {
+            Action::StartGame { players } => self.start_game(*players, rng, events),
+            Action::PlayCard { player_idx, details } => self.play_card(*player_idx, details, events),
+            Action::MakeChoice { .. } if self.state != CompactState::InProgress => Err(GameError::GameNotInProgress),
+            Action::MakeChoice { .. } => Err(GameError::NoChoicePending),
+            Action::Forfeit { player_idx } => self.forfeit(*player_idx, events),
+            Action::Timeout { player_idx } => self.time_out(*player_idx, events),
+        }
+    }
+
+    /// Get every play that the given player could legally make, in the same order as
+    /// `Game::legal_plays`.
+    pub fn legal_plays(&self, player_idx: usize) -> Vec<PlayCardDetails> {
+        if self.current_player() != Some(player_idx) {
+            return Vec::new();
+        }
+
+        let mut hand = self.hands[player_idx].iter().filter_map(|&c| unpack(c)).collect::<Vec<_>>();
+        hand.sort();
+        hand.dedup();
+
+        let mut plays = Vec::new();
+        for card in hand {
+            if self.is_player_allowed_to_play_card(player_idx, card).is_err() {
+                continue;
+            }
+
+            let targets = if card.has_target() {
+                let targets = self.unprotected_targets(player_idx, card == Card::Prince);
+                if targets == 0 {
+                    vec![None]
+                } else {
+                    (0..self.num_players()).filter(|&idx| targets & (1 << idx) != 0).map(Some).collect()
+                }
+            } else {
+                vec![None]
+            };
+
+            for target_idx in targets {
+                if card == Card::Guard && target_idx.is_some() {
+                    plays.extend(Card::CLASSIC.iter()
+                        .filter(|&&guess| guess != Card::Guard)
+                        .filter_map(|&guess| PlayCardDetails::from_parts(card, target_idx, Some(guess))));
+                } else {
+                    plays.extend(PlayCardDetails::from_parts(card, target_idx, None));
+                }
+            }
+        }
+
+        plays
+    }
+
+    /// Start a new game with the given number of players.
+    fn start_game<R, E>(&mut self, players: usize, rng: &mut R, events: &mut E) -> Result<(), GameError>
+        where R: Rng + ?Sized, E: Extend<Event>
+    {
+        if !(2..=MAX_PLAYERS).contains(&players) {
+            return Err(GameError::InvalidNumberOfPlayers(players));
+        }
+
+        *self = Self::new();
+        events.extend(Some(Event::NewGame { players }));
+
+        // Shuffle the deck in exactly the same way as `Game`, then pack it.
+        let mut deck = Deck::new();
+        deck.shuffle(rng);
+        for (idx, &card) in deck.cards().iter().enumerate() {
+            self.deck |= (pack(card) as u64) << (4 * idx);
+        }
+        self.deck_len = deck.len() as u8;
+
+        self.players = players as u8;
+        self.active = (1 << players) - 1;
+        events.extend((0..players).map(|player_idx| Event::RegisterPlayer { player_idx }));
+
+        self.burned_card = self.pop_deck();
+        events.extend(Some(Event::BurnCard {}));
+
+        if players == 2 {
+            for _ in 0..3 {
+                let card = unpack(self.pop_deck()).ok_or(GameError::InconsistentState)?;
+                events.extend(Some(Event::RemoveCardFromGame { card }));
+            }
+        }
+
+        for player_idx in 0..players {
+            events.extend(Some(self.draw_and_give_card_to_player(player_idx)?));
+        }
+        events.extend(Some(self.draw_and_give_card_to_player(0)?));
+        self.start_player_turn(0, events);
+
+        self.state = CompactState::InProgress;
+        Ok(())
+    }
+
+    /// Carry out a player playing a card.
+    fn play_card<E: Extend<Event>>(&mut self, player_idx: usize, details: &PlayCardDetails, events: &mut E) -> Result<(), GameError> {
+        use PlayCardDetails::*;
+
+        if self.state != CompactState::InProgress {
+            return Err(GameError::GameNotInProgress);
+        }
+        if player_idx >= self.num_players() {
+            return Err(GameError::PlayerDoesNotExist(player_idx));
+        }
+        if self.turn_counter as usize != player_idx {
+            return Err(GameError::PlayedOutOfTurn(player_idx));
+        }
+        let card = details.card();
+        self.is_target_valid(player_idx, details.target(), card)?;
+        self.is_player_allowed_to_play_card(player_idx, card)?;
+        if !self.remove_from_hand(player_idx, card) {
+            return Err(GameError::PlayerDoesNotHaveCard(player_idx, card));
+        }
+        self.add_discard(player_idx, card);
+        events.extend(Some(Event::PlayCard { player_idx, card }));
+
+        match *details {
+            PlayGuard { target_idx: Some(target_idx), guess } => {
+                events.extend(Some(Event::Guess { target_idx, guess }));
+                if self.hands[target_idx].contains(&pack(guess)) {
+                    self.eliminate_and_reveal(target_idx, EliminationReason::Guessed { by_idx: player_idx }, events)?;
+                }
+            }
+            PlayPriest { target_idx: Some(target_idx) } => {
+                let card = self.single_card(target_idx)?;
+                events.extend(Some(Event::ShowCard { player_idx, target_idx, card }));
+            }
+            PlayBaron { target_idx: Some(target_idx) } => {
+                let player_card = self.single_card(player_idx)?;
+                let target_card = self.single_card(target_idx)?;
+                events.extend(Some(Event::CompareHands { player_idx, player_card, target_idx, target_card }));
+                if player_card < target_card {
+                    self.eliminate_and_reveal(player_idx, EliminationReason::LostComparison { by_idx: target_idx }, events)?;
+                } else if target_card < player_card {
+                    self.eliminate_and_reveal(target_idx, EliminationReason::LostComparison { by_idx: player_idx }, events)?;
+                }
+            }
+            PlayHandmaid {} => {
+                self.protected |= 1 << player_idx;
+                events.extend(Some(Event::ProtectionGranted { player_idx }));
+            }
+            PlayPrince { target_idx } => {
+                let card = self.take_card(target_idx)?;
+                events.extend(Some(Event::DiscardCard { target_idx, card }));
+                if card == Card::Princess {
+                    self.active &= !(1 << target_idx);
+                    events.extend(Some(Event::EliminatePlayer { player_idx: target_idx, reason: EliminationReason::Discarded { by_idx: player_idx, card } }));
+                } else {
+                    events.extend(Some(self.draw_and_give_card_to_player(target_idx)?));
+                }
+            }
+            PlayKing { target_idx: Some(target_idx) } => {
+                let player_card = self.single_card(player_idx)?;
+                let target_card = self.single_card(target_idx)?;
+                self.hands[player_idx] = [pack(target_card), 0];
+                self.hands[target_idx] = [pack(player_card), 0];
+                events.extend(Some(Event::SwapHands { player_idx, player_card, target_idx, target_card }));
+            }
+            PlayPrincess {} => {
+                self.eliminate_and_reveal(player_idx, EliminationReason::Played { card: Card::Princess }, events)?;
+            }
+            _ if card.has_target() => {
+                events.extend(Some(Event::NoValidTarget { player_idx, card }));
+            }
+            _ => {}
+        }
+        self.end_turn(player_idx, events)
+    }
+
+    /// Carry out a player forfeiting the game.
+    fn forfeit<E: Extend<Event>>(&mut self, player_idx: usize, events: &mut E) -> Result<(), GameError> {
+        if self.state != CompactState::InProgress {
+            return Err(GameError::GameNotInProgress);
+        }
+        if player_idx >= self.num_players() {
+            return Err(GameError::PlayerDoesNotExist(player_idx));
+        }
+        if self.active & (1 << player_idx) == 0 {
+            return Err(GameError::PlayerEliminated(player_idx));
+        }
+        self.remove_player(player_idx, EliminationReason::Forfeit, events)
+    }
+
+    /// Carry out a player running out of time to make their play.
+    fn time_out<E: Extend<Event>>(&mut self, player_idx: usize, events: &mut E) -> Result<(), GameError> {
+        if self.state != CompactState::InProgress {
+            return Err(GameError::GameNotInProgress);
+        }
+        if player_idx >= self.num_players() {
+            return Err(GameError::PlayerDoesNotExist(player_idx));
+        }
+        if self.current_player() != Some(player_idx) {
+            return Err(GameError::PlayedOutOfTurn(player_idx));
+        }
+        self.remove_player(player_idx, EliminationReason::Timeout, events)
+    }
+
+    /// Eliminate a player who leaves the game, revealing their hand, and end their turn if it was
+    /// their turn or the game if only one player remains.
+    fn remove_player<E: Extend<Event>>(&mut self, player_idx: usize, reason: EliminationReason, events: &mut E) -> Result<(), GameError> {
+        self.active &= !(1 << player_idx);
+        events.extend(Some(Event::EliminatePlayer { player_idx, reason }));
+        let hand = self.hands[player_idx];
+        self.hands[player_idx] = [0; 2];
+        for card in hand.iter().rev().filter_map(|&c| unpack(c)) {
+            self.add_discard(player_idx, card);
+            events.extend(Some(Event::RevealCard { player_idx, card }));
+        }
+
+        if self.turn_counter as usize == player_idx {
+            self.end_turn(player_idx, events)
+        } else if self.active.count_ones() == 1 {
+            self.end_game(vec![self.active.trailing_zeros() as usize], WinReason::LastPlayerStanding, events)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// End the given player's turn, and then either the game or the turn of the next player.
+    fn end_turn<E: Extend<Event>>(&mut self, player_idx: usize, events: &mut E) -> Result<(), GameError> {
+        events.extend(Some(Event::TurnEnded { player_idx }));
+
+        if self.active.count_ones() == 1 {
+            self.end_game(vec![self.active.trailing_zeros() as usize], WinReason::LastPlayerStanding, events)
+        } else if self.deck_len == 0 {
+            let (winners, reason) = self.calculate_winners()?;
+            self.end_game(winners, reason, events)
+        } else {
+            let next_player = self.next_player();
+            events.extend(Some(self.draw_and_give_card_to_player(next_player)?));
+            self.start_player_turn(next_player, events);
+            Ok(())
+        }
+    }
+
+    /// Check the the player is allowed to play a particular card.
+    fn is_player_allowed_to_play_card(&self, player_idx: usize, card: Card) -> Result<(), GameError> {
+        if (card == Card::Prince || card == Card::King) && self.hands[player_idx].contains(&pack(Card::Countess)) {
+            return Err(GameError::CannotPlayWhileHoldingCountess(card));
+        }
+        Ok(())
+    }
+
+    /// Check that the target player exists and is allowed to be targeted by the given card.
+    fn is_target_valid(&self, player_idx: usize, target_idx: Option<usize>, card: Card) -> Result<(), GameError> {
+        if card.has_target() && self.unprotected_targets(player_idx, card == Card::Prince) != 0 && target_idx.is_none() {
+            return Err(GameError::MustProvideTarget(card));
+        }
+        if card != Card::Prince && target_idx == Some(player_idx) {
+            return Err(GameError::CannotTargetSelf(card));
+        }
+        if let Some(idx) = target_idx {
+            if idx >= self.num_players() {
+                return Err(GameError::PlayerDoesNotExist(idx));
+            }
+            if self.protected & (1 << idx) != 0 {
+                return Err(GameError::CannotTargetProtectedPlayer);
+            }
+            if self.active & (1 << idx) == 0 {
+                return Err(GameError::CannotTargetEliminatedPlayer);
+            }
+        }
+        Ok(())
+    }
+
+    /// A bitset of the players who are potential targets for an action.
+    fn unprotected_targets(&self, player_idx: usize, include_self: bool) -> u8 {
+        let targets = self.active & !self.protected;
+        if include_self { targets } else { targets & !(1 << player_idx) }
+    }
+
+    /// Remove the top card of the deck, returning 0 if the deck is empty.
+    fn pop_deck(&mut self) -> PackedCard {
+        if self.deck_len == 0 {
+            return 0;
+        }
+        self.deck_len -= 1;
+        let shift = 4 * self.deck_len as u64;
+        let card = ((self.deck >> shift) & 0xF) as PackedCard;
+        self.deck &= !(0xF << shift);
+        card
+    }
+
+    /// Draw a card, using the burned card if the deck is empty, and deal it to a player.
+    fn draw_and_give_card_to_player(&mut self, player_idx: usize) -> Result<Event, GameError> {
+        let mut card = self.pop_deck();
+        if card == 0 {
+            card = self.burned_card;
+            self.burned_card = 0;
+        }
+        let card = unpack(card).ok_or(GameError::InconsistentState)?;
+        self.give_card(player_idx, card);
+        Ok(Event::DealCard { player_idx, card })
+    }
+
+    /// Add a card to a player's hand, keeping the higher card in the first slot.
+    fn give_card(&mut self, player_idx: usize, card: Card) {
+        let hand = &mut self.hands[player_idx];
+        hand[1] = pack(card);
+        if hand[0] < hand[1] {
+            hand.swap(0, 1);
+        }
+    }
+
+    /// Begin a player's turn, ending any protection they had.
+    fn start_player_turn<E: Extend<Event>>(&mut self, player_idx: usize, events: &mut E) {
+        self.turn_counter = player_idx as u8;
+        if self.protected & (1 << player_idx) != 0 {
+            self.protected &= !(1 << player_idx);
+            events.extend(Some(Event::ProtectionExpired { player_idx }));
+        }
+        events.extend(Some(Event::ReadyToPlay { player_idx }));
+    }
+
+    /// Remove one copy of a card from a player's hand, returning whether they were holding it.
+    fn remove_from_hand(&mut self, player_idx: usize, card: Card) -> bool {
+        let hand = &mut self.hands[player_idx];
+        match hand.iter().position(|&c| c == pack(card)) {
+            Some(slot) => {
+                hand[slot] = 0;
+                if slot == 0 {
+                    hand.swap(0, 1);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the single card held by a player, or an error if they are not holding exactly one card.
+    fn single_card(&self, player_idx: usize) -> Result<Card, GameError> {
+        match self.hands[player_idx] {
+            [card, 0] => unpack(card).ok_or(GameError::InconsistentState),
+            _ => Err(GameError::InconsistentState),
+        }
+    }
+
+    /// Take the single card held by a player and add it to their discards.
+    fn take_card(&mut self, player_idx: usize) -> Result<Card, GameError> {
+        let card = self.single_card(player_idx)?;
+        self.hands[player_idx] = [0; 2];
+        self.add_discard(player_idx, card);
+        Ok(card)
+    }
+
+    /// Add a card to a player's discards.
+    fn add_discard(&mut self, player_idx: usize, card: Card) {
+        self.discards[player_idx] += 1 << (3 * (card.number() - 1));
+    }
+
+    /// Get the total value of the cards a player has discarded.
+    fn value_of_discards(&self, player_idx: usize) -> u32 {
+        Card::CLASSIC.iter().map(|&card| {
+            let count = (self.discards[player_idx] >> (3 * (card.number() - 1))) & 0b111;
+            count * card.value()
+        }).sum()
+    }
+
+    /// Eliminate a player for the given reason and reveal the card in their hand.
+    fn eliminate_and_reveal<E: Extend<Event>>(&mut self, player_idx: usize, reason: EliminationReason, events: &mut E) -> Result<(), GameError> {
+        self.active &= !(1 << player_idx);
+        events.extend(Some(Event::EliminatePlayer { player_idx, reason }));
+        let card = self.take_card(player_idx)?;
+        events.extend(Some(Event::RevealCard { player_idx, card }));
+        Ok(())
+    }
+
+    /// Work out whose turn it should be next in the game.
+    fn next_player(&self) -> usize {
+        let mut player_idx = (self.turn_counter as usize + 1) % self.num_players();
+        while self.active & (1 << player_idx) == 0 {
+            player_idx = (player_idx + 1) % self.num_players();
+        }
+        player_idx
+    }
+
+    /// Given that the deck is empty but no player has won outright, determine the winners.
+    fn calculate_winners(&self) -> Result<(Vec<usize>, WinReason), GameError> {
+        let active = (0..self.num_players()).filter(|&idx| self.active & (1 << idx) != 0);
+        let scores = active.map(|idx| Ok((idx, (self.single_card(idx)?, self.value_of_discards(idx)))))
+            .collect::<Result<Vec<_>, GameError>>()?;
+        let high_score = scores.iter().map(|&(_, score)| score).max().ok_or(GameError::InconsistentState)?;
+        let winners = scores.iter().filter(|&&(_, score)| score == high_score).map(|&(idx, _)| idx).collect::<Vec<_>>();
+        let reason = if winners.len() > 1 {
+            WinReason::Tie
+        } else if scores.iter().filter(|&&(_, score)| score.0 == high_score.0).count() > 1 {
+            WinReason::DiscardTotal
+        } else {
+            WinReason::HighestScore
+        };
+        Ok((winners, reason))
+    }
+
+    /// End the game, revealing the hands of the players left in it. A player left holding two
+    /// cards, their turn having been cut short, reveals the higher one.
+    fn end_game<E: Extend<Event>>(&mut self, winner_indices: Vec<usize>, reason: WinReason, events: &mut E) -> Result<(), GameError> {
+        self.state = CompactState::Complete;
+        for player_idx in (0..self.num_players()).filter(|&idx| self.active & (1 << idx) != 0) {
+            let card = unpack(self.hands[player_idx][0]).ok_or(GameError::InconsistentState)?;
+            events.extend(Some(Event::FinalHand { player_idx, card, score: card.value(), discard_total: self.value_of_discards(player_idx) }));
+        }
+        events.extend(Some(Event::WinnersDecided { reason }));
+        events.extend(Some(Event::GameOver { winner_indices }));
+        Ok(())
+    }
+}
+
+impl Default for CompactGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A reason why a `Game` cannot be converted into a `CompactGame`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum CompactError {
+
+    /// The game has more than `MAX_PLAYERS` players.
+    TooManyPlayers(usize),
+
+    /// The game is not played with the classic cards.
+    NotClassic,
+
+    /// Some of the cards in the game have effects other than their standard ones.
+    NonStandardEffects,
+}
+
+impl fmt::Display for CompactError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use CompactError::*;
+        match self {
+            TooManyPlayers(players) => write!(f, "A compact game can have at most {} players, not {}.", MAX_PLAYERS, players),
+            NotClassic => write!(f, "Only games played with the classic cards can be made compact."),
+            NonStandardEffects => write!(f, "Only games in which every card has its standard effect can be made compact."),
+        }
+    }
+}
+
+impl Error for CompactError {}
+
+impl TryFrom<&Game> for CompactGame {
+    type Error = CompactError;
+
+    /// Convert a `Game` into a `CompactGame` in the same position. This fails if the game has more
+    /// than `MAX_PLAYERS` players, or is not played under the classic ruleset with the standard
+    /// effect for every card.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::convert::TryFrom;
+    /// # use love_letter::action::Action;
+    /// # use love_letter::compact::{CompactError, CompactGame};
+    /// # use love_letter::game::Game;
+    /// # use love_letter::ruleset::Ruleset;
+    /// let mut game = Game::with_seed(0);
+    /// game.perform_action(&Action::StartGame { players: 3 }).unwrap();
+    /// let compact = CompactGame::try_from(&game).unwrap();
+    /// assert_eq!(compact.legal_plays(0), game.legal_plays(0));
+    ///
+    /// let mut game = Game::with_seed(0).with_ruleset(Ruleset::Premium);
+    /// game.perform_action(&Action::StartGame { players: 3 }).unwrap();
+    /// assert_eq!(CompactGame::try_from(&game), Err(CompactError::NotClassic));
+    /// ```
+    fn try_from(game: &Game) -> Result<Self, CompactError> {
+        let mut compact = Self::new();
+        if !game.has_started() {
+            return Ok(compact);
+        }
+
+        if game.num_players() > MAX_PLAYERS {
+            return Err(CompactError::TooManyPlayers(game.num_players()));
+        }
+        if game.ruleset() != Ruleset::Classic || game.custom_deck().is_some() {
+            return Err(CompactError::NotClassic);
+        }
+        if !game.effects().is_standard() {
+            return Err(CompactError::NonStandardEffects);
+        }
+        for (idx, &card) in game.deck_cards().iter().enumerate() {
+            compact.deck |= (pack(card) as u64) << (4 * idx);
+        }
+        compact.deck_len = game.deck_cards().len() as u8;
+        compact.burned_card = game.burned_card().map_or(0, pack);
+        compact.players = game.num_players() as u8;
+        compact.turn_counter = game.turn_counter() as u8;
+        compact.state = if game.is_over() { CompactState::Complete } else { CompactState::InProgress };
+
+        for (idx, player) in game.players().iter().enumerate() {
+            for &card in player.hand() {
+                compact.give_card(idx, card);
+            }
+            for card in player.discards() {
+                compact.add_discard(idx, card);
+            }
+            if player.active() {
+                compact.active |= 1 << idx;
+            }
+            if player.protected() {
+                compact.protected |= 1 << idx;
+            }
+        }
+
+        Ok(compact)
+    }
+}
+
+/// A disagreement between `Game` and `CompactGame` found by `cross_check`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CrossCheckFailure {
+
+    /// The index of the action at which the engines disagreed.
+    pub step: usize,
+
+    /// The result of the action according to `Game`.
+    pub expected: Result<Vec<Event>, GameError>,
+
+    /// The result of the action according to `CompactGame`.
+    pub actual: Result<Vec<Event>, GameError>,
+
+    /// Whether the state of the two engines differed after the action, even if the results agreed.
+    pub state_differs: bool,
+}
+
+impl fmt::Display for CrossCheckFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Engines disagree at step {}: expected {:?}, got {:?}", self.step, self.expected, self.actual)?;
+        if self.state_differs {
+            write!(f, " (game states differ)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Play the given actions through both a `Game` and a `CompactGame`, with decks shuffled using the
+/// same seed, and check that they produce identical events and errors, and end up in the same
+/// state, after every action.
+///
+/// # Examples
+///
+/// ```
+/// # use rand::prelude::*;
+/// # use love_letter::action::Action;
+/// # use love_letter::compact;
+/// # use love_letter::environment;
+/// # use love_letter::game::Game;
+/// // Play a large number of games using a mixture of random legal plays, arbitrary (usually
+/// // illegal) actions, and players forfeiting or running out of time, and check that both engines
+/// // agree on every one of them.
+/// let space = environment::action_space();
+/// let mut rng = StdRng::seed_from_u64(0);
+/// for seed in 0..500 {
+///     let mut game = Game::with_seed(seed);
+///     let mut actions = vec![Action::StartGame { players: rng.gen_range(2, 5) }];
+///     game.perform_action(&actions[0]).unwrap();
+///     while let Some(player_idx) = game.current_player() {
+///         let action = match rng.gen_range(0, 20) {
+///             0 => Action::Forfeit { player_idx: rng.gen_range(0, 5) },
+///             1 => Action::Timeout { player_idx: rng.gen_range(0, 5) },
+///             2..=10 => Action::PlayCard { player_idx: rng.gen_range(0, 5), details: space.choose(&mut rng).unwrap().clone() },
+///             _ => Action::PlayCard { player_idx, details: game.legal_plays(player_idx).choose(&mut rng).unwrap().clone() },
+///         };
+///         let _ = game.perform_action(&action);
+///         actions.push(action);
+///     }
+///     compact::cross_check(seed, &actions).unwrap();
+/// }
+/// ```
+pub fn cross_check(seed: u64, actions: &[Action]) -> Result<(), CrossCheckFailure> {
+    let mut game = Game::with_seed(seed);
+    let mut compact = CompactGame::new();
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for (step, action) in actions.iter().enumerate() {
+        let expected = game.perform_action(action);
+        let mut events = Vec::new();
+        let actual = compact.perform_action(action, &mut rng, &mut events).map(|()| events);
+        let state_differs = CompactGame::try_from(&game) != Ok(compact);
+        if expected != actual || state_differs {
+            return Err(CrossCheckFailure { step, expected, actual, state_differs });
+        }
+    }
+
+    Ok(())
+}
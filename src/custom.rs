@@ -0,0 +1,379 @@
+//! Custom decks, whose cards are defined as data rather than in code.
+//!
+//! A `CustomDeck` lists the cards in the deck, giving each a name, a rank and a number of copies.
+//! The effect of each card is made up of a sequence of primitives, which are carried out in order
+//! when the card is played:
+//!
+//! * `guess` - guess the value of the target's card, eliminating them if the guess is right.
+//! * `compare` - compare hands with the target, eliminating whoever holds the lower card.
+//! * `peek` - look at the target's hand.
+//! * `swap` - swap hands with the target.
+//! * `force_discard` - make the target discard their hand and, unless that eliminates them, draw a
+//!   new card. The player may choose themselves as the target if this is the card's only
+//!   primitive with a target.
+//! * `protect` - protect the player until their next turn.
+//! * `eliminate_self_if_discarded` - eliminate the player, whether the card is played or they are
+//!   made to discard it.
+//!
+//! Each primitive may appear at most once in a card's effect. A card with any primitive other than
+//! the last two is played on another player, and has no effect at all if nobody can be targeted.
+//! Once the player or the target has been eliminated, the rest of the card's primitives are
+//! skipped. If a card eliminates everybody still in the round, the round ends with no winner.
+//!
+//! The cards of a custom deck are `Card::Custom`, numbered by their position in the list. A game is
+//! told to use a custom deck with `Game::with_deck`, and the deck is checked when each game is
+//! started. With the `decks` feature, a deck can be loaded from a TOML or JSON file:
+//!
+//! ```toml
+//! [[cards]]
+//! name = "Spy"
+//! rank = 1
+//! copies = 6
+//! effect = ["guess"]
+//!
+//! [[cards]]
+//! name = "Informant"
+//! rank = 2
+//! copies = 4
+//! effect = ["peek", "protect"]
+//!
+//! [[cards]]
+//! name = "Heir"
+//! rank = 5
+//! copies = 2
+//! effect = ["eliminate_self_if_discarded"]
+//! ```
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "decks")]
+use std::fs;
+#[cfg(feature = "decks")]
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::action::PlayCardDetails;
+use crate::card::Card;
+use crate::effect::{CardEffect, EffectContext, EffectRegistry, Targeting};
+use crate::event::{EliminationReason, Event};
+use crate::game::GameError;
+
+/// The largest number of distinct cards in a custom deck.
+pub const MAX_CARDS: usize = u8::MAX as usize + 1;
+
+/// One of the building blocks out of which the effect of a custom card is made.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Primitive {
+
+    /// Guess the value of the target's card, eliminating them if the guess is right.
+    Guess,
+
+    /// Compare hands with the target, eliminating whoever holds the lower card.
+    Compare,
+
+    /// Look at the target's hand.
+    Peek,
+
+    /// Swap hands with the target.
+    Swap,
+
+    /// Make the target discard their hand and draw a new card.
+    ForceDiscard,
+
+    /// Protect the player until their next turn.
+    Protect,
+
+    /// Eliminate the player, whether the card is played or they are made to discard it.
+    EliminateSelfIfDiscarded,
+}
+
+impl Primitive {
+
+    /// Returns whether this primitive acts on a target.
+    fn has_target(self) -> bool {
+        !matches!(self, Self::Protect | Self::EliminateSelfIfDiscarded)
+    }
+}
+
+/// The definition of a single card in a custom deck.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct CustomCard {
+
+    /// The name of the card, which must be unique within the deck.
+    pub name: String,
+
+    /// The value of the card, used when comparing hands, checking guesses and scoring the end of a
+    /// round.
+    pub rank: u32,
+
+    /// The number of copies of the card in the deck.
+    pub copies: usize,
+
+    /// The primitives which make up the effect of the card, in the order they are carried out.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub effect: Vec<Primitive>,
+}
+
+/// A deck made up of custom cards.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct CustomDeck {
+
+    /// The cards in the deck. The card at position `n` is `Card::Custom(n)`.
+    pub cards: Vec<CustomCard>,
+}
+
+impl CustomDeck {
+
+    /// Parse a deck written in TOML.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::custom::{CustomDeck, Primitive};
+    /// let deck = CustomDeck::from_toml(r#"
+    ///     [[cards]]
+    ///     name = "Informant"
+    ///     rank = 2
+    ///     copies = 4
+    ///     effect = ["peek", "protect"]
+    /// "#).unwrap();
+    /// assert_eq!(deck.cards[0].effect, vec![Primitive::Peek, Primitive::Protect]);
+    /// assert!(CustomDeck::from_toml("cards = 3").is_err());
+    /// ```
+    #[cfg(feature = "decks")]
+    pub fn from_toml(text: &str) -> Result<Self, DeckError> {
+        toml::from_str(text).map_err(|err| DeckError::Load(err.to_string()))
+    }
+
+    /// Parse a deck written in JSON.
+    #[cfg(feature = "decks")]
+    pub fn from_json(text: &str) -> Result<Self, DeckError> {
+        serde_json::from_str(text).map_err(|err| DeckError::Load(err.to_string()))
+    }
+
+    /// Load a deck from a file, which is parsed as JSON if it has a `.json` extension and as TOML
+    /// otherwise.
+    #[cfg(feature = "decks")]
+    pub fn load(path: &Path) -> Result<Self, DeckError> {
+        let text = fs::read_to_string(path).map_err(|err| DeckError::Load(err.to_string()))?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Self::from_json(&text),
+            _ => Self::from_toml(&text),
+        }
+    }
+
+    /// Get the definition of the given card, if it belongs to this deck.
+    pub fn card(&self, card: Card) -> Option<&CustomCard> {
+        match card {
+            Card::Custom(id) => self.cards.get(id as usize),
+            _ => None,
+        }
+    }
+
+    /// Get every card in the deck, with repeats, in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::card::Card;
+    /// # use love_letter::custom::{CustomCard, CustomDeck, Primitive};
+    /// let deck = CustomDeck { cards: vec![
+    ///     CustomCard { name: "Spy".to_string(), rank: 1, copies: 2, effect: vec![Primitive::Guess] },
+    ///     CustomCard { name: "Heir".to_string(), rank: 5, copies: 1, effect: vec![] },
+    /// ] };
+    /// assert_eq!(deck.all_cards(), vec![Card::Custom(0), Card::Custom(0), Card::Custom(1)]);
+    /// ```
+    pub fn all_cards(&self) -> Vec<Card> {
+        self.cards.iter().take(MAX_CARDS).enumerate()
+            .flat_map(|(id, card)| std::iter::repeat(Card::Custom(id as u8)).take(card.copies))
+            .collect()
+    }
+
+    /// Check that the deck is well-formed and has enough cards for a game with the given number of
+    /// players: one to burn, three more to remove in a two-player game, one for each player and
+    /// one for the first player to draw.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::custom::{CustomCard, CustomDeck, DeckError, Primitive};
+    /// let deck = CustomDeck { cards: vec![
+    ///     CustomCard { name: "Spy".to_string(), rank: 1, copies: 5, effect: vec![Primitive::Guess] },
+    ///     CustomCard { name: "Heir".to_string(), rank: 5, copies: 1, effect: vec![] },
+    /// ] };
+    /// assert_eq!(deck.validate(4), Ok(()));
+    /// assert_eq!(deck.validate(2), Err(DeckError::NotEnoughCards { required: 7, available: 6 }));
+    /// ```
+    pub fn validate(&self, players: usize) -> Result<(), DeckError> {
+        if self.cards.len() > MAX_CARDS {
+            return Err(DeckError::TooManyCards(self.cards.len()));
+        }
+        for (id, card) in self.cards.iter().enumerate() {
+            if card.name.trim().is_empty() {
+                return Err(DeckError::MissingName(id));
+            }
+            if self.cards[..id].iter().any(|other| other.name == card.name) {
+                return Err(DeckError::DuplicateName(card.name.clone()));
+            }
+            if card.copies == 0 {
+                return Err(DeckError::NoCopies(card.name.clone()));
+            }
+            for (idx, &primitive) in card.effect.iter().enumerate() {
+                if card.effect[..idx].contains(&primitive) {
+                    return Err(DeckError::RepeatedPrimitive(card.name.clone(), primitive));
+                }
+            }
+        }
+
+        let required = players + 2 + if players == 2 { 3 } else { 0 };
+        let available = self.cards.iter().map(|card| card.copies).sum();
+        if available < required {
+            return Err(DeckError::NotEnoughCards { required, available });
+        }
+        Ok(())
+    }
+
+    /// Register the effect of every card in the deck.
+    pub(crate) fn register_effects(&self, registry: &mut EffectRegistry) {
+        for (id, definition) in self.cards.iter().take(MAX_CARDS).enumerate() {
+            registry.register(CustomEffect { card: Card::Custom(id as u8), definition: definition.clone() });
+        }
+    }
+}
+
+/// The effect of a custom card, carrying out its primitives in order.
+#[derive(Debug)]
+struct CustomEffect {
+    card: Card,
+    definition: CustomCard,
+}
+
+impl CustomEffect {
+
+    /// Returns whether the card has the given primitive.
+    fn has(&self, primitive: Primitive) -> bool {
+        self.definition.effect.contains(&primitive)
+    }
+}
+
+impl CardEffect for CustomEffect {
+    fn card(&self) -> Card {
+        self.card
+    }
+
+    fn name(&self) -> String {
+        self.definition.name.clone()
+    }
+
+    fn value(&self) -> u32 {
+        self.definition.rank
+    }
+
+    fn targeting(&self) -> Targeting {
+        let targeted = self.definition.effect.iter().filter(|primitive| primitive.has_target()).collect::<Vec<_>>();
+        if targeted.is_empty() {
+            Targeting::NONE
+        } else if targeted.iter().all(|&&primitive| primitive == Primitive::ForceDiscard) {
+            Targeting::ANY
+        } else {
+            Targeting::OTHER
+        }
+    }
+
+    fn takes_guess(&self) -> bool {
+        self.has(Primitive::Guess)
+    }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let mut events = Vec::new();
+        for &primitive in &self.definition.effect {
+            let target_idx = details.target();
+            let target_active = target_idx.is_some_and(|target_idx| context.is_active(target_idx));
+            if !context.is_active(player_idx) || (primitive.has_target() && !target_active) {
+                break;
+            }
+            let target_idx = target_idx.unwrap_or(player_idx);
+
+            match primitive {
+                Primitive::Guess => {
+                    let guess = details.guess().ok_or(GameError::MustProvideGuess(self.card))?;
+                    events.push(Event::Guess { target_idx, guess });
+                    if context.is_holding_value(target_idx, context.value(guess)) {
+                        events.extend(context.eliminate(target_idx, EliminationReason::Guessed { by_idx: player_idx })?);
+                    }
+                }
+                Primitive::Compare => events.extend(context.compare(player_idx, target_idx, Ordering::Less)?),
+                Primitive::Peek => events.push(context.show(player_idx, target_idx)?),
+                Primitive::Swap => events.push(context.swap(player_idx, target_idx)?),
+                Primitive::ForceDiscard => {
+                    events.extend(context.discard(target_idx, player_idx)?);
+                    if context.is_active(target_idx) {
+                        events.push(context.draw(target_idx)?);
+                    }
+                }
+                Primitive::Protect => events.push(context.protect(player_idx)),
+                Primitive::EliminateSelfIfDiscarded => events.extend(context.eliminate(player_idx, EliminationReason::Played { card: self.card })?),
+            }
+        }
+        Ok(events)
+    }
+
+    fn on_discarded(&self, context: &mut EffectContext, player_idx: usize, by_idx: usize) -> Result<Vec<Event>, GameError> {
+        if self.has(Primitive::EliminateSelfIfDiscarded) {
+            context.eliminate(player_idx, EliminationReason::Discarded { by_idx, card: self.card })
+        } else {
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// A reason why a custom deck cannot be used.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum DeckError {
+
+    /// The deck could not be read or parsed.
+    Load(String),
+
+    /// The deck has more distinct cards than can be told apart.
+    TooManyCards(usize),
+
+    /// The card at the given position in the deck has no name.
+    MissingName(usize),
+
+    /// Two cards in the deck have the same name.
+    DuplicateName(String),
+
+    /// A card is included in the deck with no copies.
+    NoCopies(String),
+
+    /// The effect of a card uses the same primitive more than once.
+    RepeatedPrimitive(String, Primitive),
+
+    /// The deck does not have enough cards for the number of players.
+    NotEnoughCards { required: usize, available: usize },
+}
+
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use DeckError::*;
+        match self {
+            Load(message) => write!(f, "Could not load deck: {}", message),
+            TooManyCards(cards) => write!(f, "The deck has {} different cards, but at most {} are allowed.", cards, MAX_CARDS),
+            MissingName(id) => write!(f, "Card {} in the deck has no name.", id),
+            DuplicateName(name) => write!(f, "There is more than one card called {} in the deck.", name),
+            NoCopies(name) => write!(f, "The deck has no copies of the {}.", name),
+            RepeatedPrimitive(name, primitive) => write!(f, "The effect of the {} uses {:?} more than once.", name, primitive),
+            NotEnoughCards { required, available } => write!(f, "The deck has {} cards, but {} are needed for this many players.", available, required),
+        }
+    }
+}
+
+impl Error for DeckError {}
@@ -3,6 +3,7 @@
 use rand::prelude::*;
 
 use crate::card::Card;
+use crate::ruleset::Ruleset;
 
 /// A Love Letter deck.
 #[derive(Clone, Debug)]
@@ -12,10 +13,15 @@ pub struct Deck {
 
 impl Deck {
 
-    /// Returns a new `Deck` with the cards in a fixed default order.
+    /// Returns a new classic `Deck` with the cards in a fixed default order.
     pub fn new() -> Self {
-        Self {
-            cards: vec![
+        Self::for_ruleset(Ruleset::Classic)
+    }
+
+    /// Returns a new `Deck` for the given ruleset with the cards in a fixed default order.
+    pub fn for_ruleset(ruleset: Ruleset) -> Self {
+        let cards = match ruleset {
+            Ruleset::Classic => vec![
                 Card::Guard, Card::Guard, Card::Guard, Card::Guard, Card::Guard,
                 Card::Priest, Card::Priest,
                 Card::Baron, Card::Baron,
@@ -25,7 +31,43 @@ impl Deck {
                 Card::Countess,
                 Card::Princess,
             ],
-        }
+            Ruleset::Premium => vec![
+                Card::Jester,
+                Card::Assassin,
+                Card::Guard, Card::Guard, Card::Guard, Card::Guard,
+                Card::Guard, Card::Guard, Card::Guard, Card::Guard,
+                Card::Priest, Card::Priest,
+                Card::Cardinal, Card::Cardinal,
+                Card::Baron, Card::Baron,
+                Card::Baroness, Card::Baroness,
+                Card::Handmaid, Card::Handmaid,
+                Card::Sycophant, Card::Sycophant,
+                Card::Prince, Card::Prince,
+                Card::Count, Card::Count,
+                Card::King,
+                Card::Constable,
+                Card::Countess,
+                Card::DowagerQueen,
+                Card::Princess,
+                Card::Bishop,
+            ],
+            Ruleset::Lovecraft => vec![
+                Card::Guard, Card::Guard, Card::Guard, Card::Guard, Card::Guard, Card::DeepOnes,
+                Card::Priest, Card::Priest, Card::GoldenMead,
+                Card::Baron, Card::Baron, Card::HoundOfTindalos,
+                Card::Handmaid, Card::Handmaid, Card::LiberIvonis,
+                Card::Prince, Card::Prince, Card::StarSpawn,
+                Card::King, Card::Nyarlathotep,
+                Card::Countess, Card::MiGo,
+                Card::Princess, Card::Cthulhu,
+            ],
+        };
+        Self { cards }
+    }
+
+    /// Returns a new `Deck` containing the given cards, with the top of the deck last.
+    pub fn from_cards(cards: Vec<Card>) -> Self {
+        Self { cards }
     }
 
     /// Checks if the deck is empty
@@ -33,10 +75,19 @@ impl Deck {
         self.cards.is_empty()
     }
 
-    /// Shuffles the cards in this `Deck` into a random order.
-    pub fn shuffle(&mut self) {
-        let mut rng = thread_rng();
-        self.cards.shuffle(&mut rng);
+    /// Shuffles the cards in this `Deck` into a random order using the given source of randomness.
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
+    }
+
+    /// Returns the number of cards remaining in the deck.
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Returns the cards in the deck, with the top of the deck last.
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
     }
 
     /// Draws the top card from the `Deck` and returns it, or `None` if it is empty.
@@ -0,0 +1,965 @@
+//! The behaviour of each card, gathered behind a common trait.
+//!
+//! Everything that makes one card different from another - who it may target, whether it takes a
+//! guess, what holding it prevents, what happens when it is played or discarded, and how it counts
+//! at the end of the round - is described by an implementation of `CardEffect`. The engine itself
+//! knows nothing about individual cards: it validates plays and carries them out by asking the
+//! effect of each card involved.
+//!
+//! Effects act on the game through an `EffectContext`, which offers the operations out of which
+//! every card is built, such as eliminating a player, forcing them to discard or showing their hand
+//! to another player. Each operation keeps the game consistent and returns the events describing
+//! what happened.
+//!
+//! The effects of the standard cards are returned by `standard`. An `EffectRegistry` maps each card
+//! to its effect, and may override the standard effect of any card, allowing house rules to be
+//! played with `Game::with_effect`.
+//!
+//! Effects decide how a card behaves, but not the shape of a play: each card in the rulesets has
+//! its own `PlayCardDetails` variant, which the engine converts to and from its targets and guess.
+//! Overriding the effect of an existing card, or building cards for a custom deck, needs no change
+//! to the engine, but adding a new card to the rulesets also means adding its variant to
+//! `PlayCardDetails` and its standard effect to `standard`.
+//!
+//! ```
+//! # use love_letter::action::{Action, PlayCardDetails};
+//! # use love_letter::card::Card;
+//! # use love_letter::effect::{CardEffect, EffectContext};
+//! # use love_letter::event::Event;
+//! # use love_letter::game::{Game, GameError};
+//! // A house rule in which the Handmaid lets its player draw another card instead of protecting them.
+//! #[derive(Debug)]
+//! struct GenerousHandmaid;
+//!
+//! impl CardEffect for GenerousHandmaid {
+//!     fn card(&self) -> Card {
+//!         Card::Handmaid
+//!     }
+//!
+//!     fn apply(&self, context: &mut EffectContext, player_idx: usize, _: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+//!         Ok(vec![context.draw(player_idx)?])
+//!     }
+//! }
+//!
+//! let game = Game::with_seed(0).with_effect(GenerousHandmaid);
+//! assert_eq!(game.effect(Card::Handmaid).targeting(), GenerousHandmaid.targeting());
+//! ```
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::action::PlayCardDetails;
+use crate::card::Card;
+use crate::choice::{Choice, ChoiceKind, PendingChoice};
+use crate::event::{EliminationReason, Event};
+use crate::game::{Game, GameError};
+
+/// The targets a card may be played on.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Targeting {
+
+    /// The number of targets which must be chosen, as long as enough players can be targeted. If
+    /// too few can be, the card is played without any targets and has no effect.
+    pub required: usize,
+
+    /// The largest number of targets which may be chosen.
+    pub max: usize,
+
+    /// Whether the player may choose themselves as a target.
+    pub include_self: bool,
+}
+
+impl Targeting {
+
+    /// A card which has no targets.
+    pub const NONE: Targeting = Targeting { required: 0, max: 0, include_self: false };
+
+    /// A card which targets one other player.
+    pub const OTHER: Targeting = Targeting { required: 1, max: 1, include_self: false };
+
+    /// A card which targets one player, who may be its player.
+    pub const ANY: Targeting = Targeting { required: 1, max: 1, include_self: true };
+
+    /// Returns whether a card with this targeting has any targets at all.
+    pub fn has_target(self) -> bool {
+        self.max > 0
+    }
+}
+
+/// A lasting choice made with a card whose effect comes later, such as the Jester.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Mark {
+
+    /// The card with which the choice was made.
+    pub card: Card,
+
+    /// The player who made the choice.
+    pub player_idx: usize,
+
+    /// The player who was chosen.
+    pub target_idx: usize,
+}
+
+/// The behaviour of a single card.
+///
+/// Only `card` must be implemented. By default, a card has no targets, takes no guess, is sane,
+/// places no restrictions on the other cards in its holder's hand, does nothing when played or
+/// discarded, and counts for its value at the end of the round.
+pub trait CardEffect: fmt::Debug + Send + Sync {
+
+    /// The card whose behaviour this is.
+    fn card(&self) -> Card;
+
+    /// The name of the card.
+    fn name(&self) -> String {
+        format!("{:?}", self.card())
+    }
+
+    /// The value of the card, used when comparing hands, checking guesses and scoring the end of a
+    /// round.
+    fn value(&self) -> u32 {
+        self.card().value()
+    }
+
+    /// Who the card may target.
+    fn targeting(&self) -> Targeting {
+        Targeting::NONE
+    }
+
+    /// Whether the card is played with a guess about its target's hand.
+    fn takes_guess(&self) -> bool {
+        false
+    }
+
+    /// Whether the card is insane, making whoever discards it insane, as in Lovecraft Letter.
+    fn is_insane(&self) -> bool {
+        false
+    }
+
+    /// Check whether a player holding this card may play the given card from the rest of their
+    /// hand, as with the Countess.
+    fn restrict(&self, _played: Card) -> Result<(), GameError> {
+        Ok(())
+    }
+
+    /// Carry out the effect of the card, which has been played by the given player with the given
+    /// details, returning the events that result. The card has already been moved to the player's
+    /// discards, and any targets it requires have been checked.
+    fn apply(&self, _context: &mut EffectContext, _player_idx: usize, _details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        Ok(Vec::new())
+    }
+
+    /// Finish the effect of the card once the choice it called for with
+    /// `EffectContext::require_choice` has been made, returning the events that result. The choice
+    /// has already been checked to be one of the options for its kind.
+    fn resolve_choice(&self, _context: &mut EffectContext, _pending: &PendingChoice, _choice: Choice) -> Result<Vec<Event>, GameError> {
+        Ok(Vec::new())
+    }
+
+    /// React to the player holding this card being made the first target of another player's play,
+    /// returning the events that replace the effect of the card played, or `None` to let it take
+    /// effect as usual.
+    fn on_targeted(&self, _context: &mut EffectContext, _holder_idx: usize, _player_idx: usize, _details: &PlayCardDetails) -> Result<Option<Vec<Event>>, GameError> {
+        Ok(None)
+    }
+
+    /// React to the given player discarding this card because of another card's effect, at the
+    /// hands of `by_idx` - the player of that card, or the player themselves if they chose to.
+    fn on_discarded(&self, _context: &mut EffectContext, _player_idx: usize, _by_idx: usize) -> Result<Vec<Event>, GameError> {
+        Ok(Vec::new())
+    }
+
+    /// React to the given player being eliminated with this card among their discards.
+    fn on_eliminated(&self, _player_idx: usize) -> Vec<Event> {
+        Vec::new()
+    }
+
+    /// React to the end of the round, won by the given players, for a mark made with this card.
+    fn on_round_end(&self, _mark: &Mark, _winner_indices: &[usize]) -> Vec<Event> {
+        Vec::new()
+    }
+
+    /// The bonus to its player's score at the end of the round for having discarded this card.
+    fn discard_bonus(&self) -> u32 {
+        0
+    }
+
+    /// Whether this card loses to the given card when hands are compared, regardless of value.
+    fn loses_to(&self, _other: Card) -> bool {
+        false
+    }
+}
+
+/// The effect of each card in a game: the standard effect, unless it has been overridden.
+#[derive(Clone, Debug, Default)]
+pub struct EffectRegistry {
+    overrides: Vec<Arc<dyn CardEffect>>,
+}
+
+impl EffectRegistry {
+
+    /// A registry in which every card has its standard effect.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the effect of the card that the given effect belongs to.
+    pub fn register<E: CardEffect + 'static>(&mut self, effect: E) {
+        let card = effect.card();
+        self.overrides.retain(|existing| existing.card() != card);
+        self.overrides.push(Arc::new(effect));
+    }
+
+    /// Check whether every card has its standard effect.
+    pub fn is_standard(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    /// Get the effect of the given card.
+    pub fn get(&self, card: Card) -> &dyn CardEffect {
+        match self.overrides.iter().find(|effect| effect.card() == card) {
+            Some(effect) => effect.as_ref(),
+            None => standard(card),
+        }
+    }
+
+    /// Compare the strength of two cards, as when two hands are compared. The card with the
+    /// higher value is stronger, unless one of them loses to the other regardless.
+    pub fn strength_cmp(&self, card: Card, other: Card) -> Ordering {
+        if self.get(card).loses_to(other) {
+            Ordering::Less
+        } else if self.get(other).loses_to(card) {
+            Ordering::Greater
+        } else {
+            self.get(card).value().cmp(&self.get(other).value())
+        }
+    }
+}
+
+/// The operations available to a card's effect while it is being carried out.
+pub struct EffectContext<'a> {
+    game: &'a mut Game,
+}
+
+impl<'a> EffectContext<'a> {
+
+    /// Create a context for carrying out effects on the given game.
+    pub(crate) fn new(game: &'a mut Game) -> Self {
+        Self { game }
+    }
+
+    /// Get the number of players in the game.
+    pub fn num_players(&self) -> usize {
+        self.game.num_players()
+    }
+
+    /// Check whether the given player is still in the game.
+    pub fn is_active(&self, player_idx: usize) -> bool {
+        self.game.players()[player_idx].active()
+    }
+
+    /// Get the single card held by the given player.
+    pub fn held_card(&self, player_idx: usize) -> Result<Card, GameError> {
+        self.game.held_card(player_idx)
+    }
+
+    /// Get the value of the given card under the effects in play.
+    pub fn value(&self, card: Card) -> u32 {
+        self.game.effect(card).value()
+    }
+
+    /// Check whether the given player holds a card with the given value.
+    pub fn is_holding_value(&self, player_idx: usize, value: u32) -> bool {
+        self.game.players()[player_idx].hand().iter().any(|&card| self.value(card) == value)
+    }
+
+    /// Check whether the given player is protected from other players' cards.
+    pub fn is_protected(&self, player_idx: usize) -> bool {
+        self.game.players()[player_idx].protected()
+    }
+
+    /// Get the number of insane cards among the given player's discards. A player who has
+    /// discarded any insane card is insane.
+    pub fn insanity(&self, player_idx: usize) -> usize {
+        self.game.players()[player_idx].discards().filter(|&card| self.game.effect(card).is_insane()).count()
+    }
+
+    /// Get the other players who are still in the game and not protected, in turn order starting
+    /// after the given player.
+    pub fn unprotected_others(&self, player_idx: usize) -> Vec<usize> {
+        let players = self.num_players();
+        (1..players).map(|offset| (player_idx + offset) % players)
+            .filter(|&idx| self.is_active(idx) && !self.is_protected(idx))
+            .collect()
+    }
+
+    /// Eliminate the given player from the game for the given reason, giving the effects of their
+    /// discards a chance to react, and reveal the card in their hand if they are holding one.
+    pub fn eliminate(&mut self, player_idx: usize, reason: EliminationReason) -> Result<Vec<Event>, GameError> {
+        let mut events = vec![self.game.eliminate_player(player_idx, reason)];
+        let mut discards = self.game.players()[player_idx].discards().collect::<Vec<_>>();
+        discards.sort();
+        discards.dedup();
+        for card in discards {
+            events.extend(self.game.effect(card).on_eliminated(player_idx));
+        }
+        if self.game.players()[player_idx].card().is_some() {
+            events.push(self.game.reveal_eliminated_player_card(player_idx)?);
+        }
+        Ok(events)
+    }
+
+    /// Make the given player discard the card in their hand at the hands of `by_idx`, giving its
+    /// effect a chance to react.
+    pub fn discard(&mut self, player_idx: usize, by_idx: usize) -> Result<Vec<Event>, GameError> {
+        let card = self.held_card(player_idx)?;
+        let mut events = vec![self.game.discard_hand(player_idx)?];
+        let effects = self.game.effects();
+        events.extend(effects.get(card).on_discarded(self, player_idx, by_idx)?);
+        Ok(events)
+    }
+
+    /// Deal the given player a card from the deck, or the burned card if the deck is empty.
+    pub fn draw(&mut self, player_idx: usize) -> Result<Event, GameError> {
+        self.game.draw_and_give_card_to_player(player_idx)
+    }
+
+    /// Show the target's hand to the player.
+    pub fn show(&mut self, player_idx: usize, target_idx: usize) -> Result<Event, GameError> {
+        let card = self.held_card(target_idx)?;
+        Ok(Event::ShowCard { player_idx, target_idx, card })
+    }
+
+    /// Make two players swap hands.
+    pub fn swap(&mut self, player_idx: usize, target_idx: usize) -> Result<Event, GameError> {
+        let player_card = self.held_card(player_idx)?;
+        let target_card = self.held_card(target_idx)?;
+        self.game.set_held_card(player_idx, target_card);
+        self.game.set_held_card(target_idx, player_card);
+        Ok(Event::SwapHands { player_idx, player_card, target_idx, target_card })
+    }
+
+    /// Compare the hands of the player and the target, eliminating whichever of them holds the
+    /// card which compares to the other's in the given way. Nobody is eliminated after a tie.
+    pub fn compare(&mut self, player_idx: usize, target_idx: usize, eliminated: Ordering) -> Result<Vec<Event>, GameError> {
+        let player_card = self.held_card(player_idx)?;
+        let target_card = self.held_card(target_idx)?;
+        let mut events = vec![Event::CompareHands { player_idx, player_card, target_idx, target_card }];
+
+        let eliminated = match self.game.effects().strength_cmp(player_card, target_card) {
+            Ordering::Equal => None,
+            ordering if ordering == eliminated => Some((player_idx, target_idx)),
+            _ => Some((target_idx, player_idx)),
+        };
+        if let Some((eliminated_idx, by_idx)) = eliminated {
+            events.extend(self.eliminate(eliminated_idx, EliminationReason::LostComparison { by_idx })?);
+        }
+
+        Ok(events)
+    }
+
+    /// Make the given player reveal a card from the top of the deck for each insane card among
+    /// their discards, stopping early rather than reveal the last card in the deck. The revealed
+    /// cards are added to their discards, and they are eliminated as soon as an insane card is
+    /// revealed.
+    pub fn insanity_check(&mut self, player_idx: usize) -> Result<Vec<Event>, GameError> {
+        let mut events = Vec::new();
+        for _ in 0..self.insanity(player_idx) {
+            let card = match self.game.reveal_top_card(player_idx) {
+                Some(card) => card,
+                None => break,
+            };
+            events.push(Event::InsanityCheck { player_idx, card });
+            if self.game.effect(card).is_insane() {
+                events.extend(self.eliminate(player_idx, EliminationReason::InsanityCheck { card })?);
+                break;
+            }
+        }
+        Ok(events)
+    }
+
+    /// Make the given player the winner of the round, which ends as soon as the card being played
+    /// has taken effect.
+    pub fn win(&mut self, player_idx: usize) {
+        self.game.set_round_winner(player_idx);
+    }
+
+    /// Get the player who has won the round outright through the card being played, if any.
+    pub fn round_winner(&self) -> Option<usize> {
+        self.game.round_winner()
+    }
+
+    /// Protect the given player until the start of their next turn.
+    pub fn protect(&mut self, player_idx: usize) -> Event {
+        self.game.protect_player(player_idx);
+        Event::ProtectionGranted { player_idx }
+    }
+
+    /// Require the next card played to target the given player, if it can.
+    pub fn force_next_target(&mut self, target_idx: usize) {
+        self.game.set_forced_target(Some(target_idx));
+    }
+
+    /// Stop the effect of the given card until the given player has made a choice, after which it
+    /// is finished by the effect's `resolve_choice`. The card's effect should do nothing further
+    /// once it has called this.
+    pub fn require_choice(&mut self, card: Card, player_idx: usize, kind: ChoiceKind) {
+        self.game.set_pending_choice(PendingChoice { card, player_idx, kind });
+    }
+
+    /// Record a choice made with a card whose effect comes later, to be resolved by its effect's
+    /// `on_round_end`.
+    pub fn mark(&mut self, card: Card, player_idx: usize, target_idx: usize) -> Event {
+        self.game.add_mark(Mark { card, player_idx, target_idx });
+        Event::ChooseTarget { player_idx, target_idx }
+    }
+}
+
+/// Get the standard effect of the given card. A custom card has no standard effect, and is given
+/// one which does nothing until its definition is registered.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::card::Card;
+/// # use love_letter::effect::{self, Targeting};
+/// assert_eq!(effect::standard(Card::King).targeting(), Targeting::OTHER);
+/// assert!(effect::standard(Card::Bishop).loses_to(Card::Princess));
+/// ```
+pub fn standard(card: Card) -> &'static dyn CardEffect {
+    match card {
+        Card::Guard => &Guard,
+        Card::Priest => &Priest,
+        Card::Baron => &Baron,
+        Card::Handmaid => &Handmaid,
+        Card::Prince => &Prince,
+        Card::King => &King,
+        Card::Countess => &Countess,
+        Card::Princess => &Princess,
+        Card::Jester => &Jester,
+        Card::Assassin => &Assassin,
+        Card::Cardinal => &Cardinal,
+        Card::Baroness => &Baroness,
+        Card::Sycophant => &Sycophant,
+        Card::Count => &Count,
+        Card::Constable => &Constable,
+        Card::DowagerQueen => &DowagerQueen,
+        Card::Bishop => &Bishop,
+        Card::DeepOnes => &DeepOnes,
+        Card::GoldenMead => &GoldenMead,
+        Card::HoundOfTindalos => &HoundOfTindalos,
+        Card::LiberIvonis => &LiberIvonis,
+        Card::StarSpawn => &StarSpawn,
+        Card::Nyarlathotep => &Nyarlathotep,
+        Card::MiGo => &MiGo,
+        Card::Cthulhu => &Cthulhu,
+        Card::Custom(id) => &UNDEFINED[id as usize],
+    }
+}
+
+/// The effects of custom cards which have not been defined, one for each possible card.
+static UNDEFINED: [Undefined; 256] = {
+    let mut effects = [Undefined(0); 256];
+    let mut id = 0;
+    while id < effects.len() {
+        effects[id] = Undefined(id as u8);
+        id += 1;
+    }
+    effects
+};
+
+/// Get the first target of a play, which the engine has checked is present.
+fn first_target(details: &PlayCardDetails) -> Result<usize, GameError> {
+    details.target().ok_or(GameError::InconsistentState)
+}
+
+/// Check whether the player of an insane card was already insane before playing it, which gives
+/// the card its insane effect. The card itself is already among their discards.
+fn was_insane(context: &EffectContext, player_idx: usize) -> bool {
+    context.insanity(player_idx) > 1
+}
+
+/// A custom card without a definition, which does nothing.
+#[derive(Copy, Clone, Debug)]
+struct Undefined(u8);
+
+impl CardEffect for Undefined {
+    fn card(&self) -> Card { Card::Custom(self.0) }
+}
+
+/// Guess another player's card, eliminating them if the guess is right.
+#[derive(Debug)]
+struct Guard;
+
+impl CardEffect for Guard {
+    fn card(&self) -> Card { Card::Guard }
+    fn targeting(&self) -> Targeting { Targeting::OTHER }
+    fn takes_guess(&self) -> bool { true }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let target_idx = first_target(details)?;
+        let guess = details.guess().ok_or(GameError::InconsistentState)?;
+        let mut events = vec![Event::Guess { target_idx, guess }];
+        if context.is_holding_value(target_idx, context.value(guess)) {
+            events.extend(context.eliminate(target_idx, EliminationReason::Guessed { by_idx: player_idx })?);
+        }
+        Ok(events)
+    }
+}
+
+/// Look at another player's hand.
+#[derive(Debug)]
+struct Priest;
+
+impl CardEffect for Priest {
+    fn card(&self) -> Card { Card::Priest }
+    fn targeting(&self) -> Targeting { Targeting::OTHER }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        Ok(vec![context.show(player_idx, first_target(details)?)?])
+    }
+}
+
+/// Compare hands with another player, eliminating whoever holds the lower card.
+#[derive(Debug)]
+struct Baron;
+
+impl CardEffect for Baron {
+    fn card(&self) -> Card { Card::Baron }
+    fn targeting(&self) -> Targeting { Targeting::OTHER }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        context.compare(player_idx, first_target(details)?, Ordering::Less)
+    }
+}
+
+/// Protect the player until their next turn.
+#[derive(Debug)]
+struct Handmaid;
+
+impl CardEffect for Handmaid {
+    fn card(&self) -> Card { Card::Handmaid }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, _details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        Ok(vec![context.protect(player_idx)])
+    }
+}
+
+/// Force any player to discard their hand and, unless that eliminates them, draw a new one.
+#[derive(Debug)]
+struct Prince;
+
+impl CardEffect for Prince {
+    fn card(&self) -> Card { Card::Prince }
+    fn targeting(&self) -> Targeting { Targeting::ANY }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let target_idx = first_target(details)?;
+        let mut events = context.discard(target_idx, player_idx)?;
+        if context.is_active(target_idx) {
+            events.push(context.draw(target_idx)?);
+        }
+        Ok(events)
+    }
+}
+
+/// Swap hands with another player.
+#[derive(Debug)]
+struct King;
+
+impl CardEffect for King {
+    fn card(&self) -> Card { Card::King }
+    fn targeting(&self) -> Targeting { Targeting::OTHER }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        Ok(vec![context.swap(player_idx, first_target(details)?)?])
+    }
+}
+
+/// No effect when played, but must be played rather than the Prince or King, or their insane
+/// versions.
+#[derive(Debug)]
+struct Countess;
+
+impl CardEffect for Countess {
+    fn card(&self) -> Card { Card::Countess }
+
+    fn restrict(&self, played: Card) -> Result<(), GameError> {
+        match played {
+            Card::Prince | Card::King | Card::StarSpawn | Card::Nyarlathotep => Err(GameError::CannotPlayWhileHoldingCountess(played)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Eliminates its player when played or discarded.
+#[derive(Debug)]
+struct Princess;
+
+impl CardEffect for Princess {
+    fn card(&self) -> Card { Card::Princess }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, _details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        context.eliminate(player_idx, EliminationReason::Played { card: Card::Princess })
+    }
+
+    fn on_discarded(&self, context: &mut EffectContext, player_idx: usize, by_idx: usize) -> Result<Vec<Event>, GameError> {
+        context.eliminate(player_idx, EliminationReason::Discarded { by_idx, card: Card::Princess })
+    }
+}
+
+/// Choose another player, and gain a token if they win the round.
+#[derive(Debug)]
+struct Jester;
+
+impl CardEffect for Jester {
+    fn card(&self) -> Card { Card::Jester }
+    fn targeting(&self) -> Targeting { Targeting::OTHER }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        Ok(vec![context.mark(Card::Jester, player_idx, first_target(details)?)])
+    }
+
+    fn on_round_end(&self, mark: &Mark, winner_indices: &[usize]) -> Vec<Event> {
+        if winner_indices.contains(&mark.target_idx) {
+            vec![Event::GainToken { player_idx: mark.player_idx, card: Card::Jester }]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Eliminates whoever targets its holder with the Guard, after which the holder discards it and
+/// draws a new card.
+#[derive(Debug)]
+struct Assassin;
+
+impl CardEffect for Assassin {
+    fn card(&self) -> Card { Card::Assassin }
+
+    fn on_targeted(&self, context: &mut EffectContext, holder_idx: usize, player_idx: usize, details: &PlayCardDetails) -> Result<Option<Vec<Event>>, GameError> {
+        let guess = match details {
+            PlayCardDetails::PlayGuard { guess, .. } => *guess,
+            _ => return Ok(None),
+        };
+        let mut events = vec![Event::Guess { target_idx: holder_idx, guess }];
+        events.extend(context.eliminate(player_idx, EliminationReason::CardEffect { by_idx: holder_idx, card: Card::Assassin })?);
+        events.extend(context.discard(holder_idx, player_idx)?);
+        events.push(context.draw(holder_idx)?);
+        Ok(Some(events))
+    }
+}
+
+/// Make any two players swap hands, then look at the hand now held by either of them.
+#[derive(Debug)]
+struct Cardinal;
+
+impl CardEffect for Cardinal {
+    fn card(&self) -> Card { Card::Cardinal }
+    fn targeting(&self) -> Targeting { Targeting { required: 2, max: 2, include_self: true } }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let targets = details.targets();
+        let (first_idx, second_idx) = match *targets {
+            [first_idx, second_idx] => (first_idx, second_idx),
+            _ => return Err(GameError::InconsistentState),
+        };
+        let events = vec![context.swap(first_idx, second_idx)?];
+
+        // A player who took part in the swap already knows what both targets now hold.
+        if player_idx != first_idx && player_idx != second_idx {
+            context.require_choice(Card::Cardinal, player_idx, ChoiceKind::LookAtHand { first_idx, second_idx });
+        }
+        Ok(events)
+    }
+
+    fn resolve_choice(&self, context: &mut EffectContext, pending: &PendingChoice, choice: Choice) -> Result<Vec<Event>, GameError> {
+        match choice {
+            Choice::LookAtHand { target_idx } => Ok(vec![context.show(pending.player_idx, target_idx)?]),
+            _ => Err(GameError::InconsistentState),
+        }
+    }
+}
+
+/// Look at the hands of one or two other players.
+#[derive(Debug)]
+struct Baroness;
+
+impl CardEffect for Baroness {
+    fn card(&self) -> Card { Card::Baroness }
+    fn targeting(&self) -> Targeting { Targeting { required: 1, max: 2, include_self: false } }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        details.targets().into_iter().map(|target_idx| context.show(player_idx, target_idx)).collect()
+    }
+}
+
+/// Choose any player, who must be targeted by the next card played if it can target them.
+#[derive(Debug)]
+struct Sycophant;
+
+impl CardEffect for Sycophant {
+    fn card(&self) -> Card { Card::Sycophant }
+    fn targeting(&self) -> Targeting { Targeting::ANY }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let target_idx = first_target(details)?;
+        context.force_next_target(target_idx);
+        Ok(vec![Event::ChooseTarget { player_idx, target_idx }])
+    }
+}
+
+/// No effect when played, but adds one to its player's score at the end of the round.
+#[derive(Debug)]
+struct Count;
+
+impl CardEffect for Count {
+    fn card(&self) -> Card { Card::Count }
+    fn discard_bonus(&self) -> u32 { 1 }
+}
+
+/// No effect when played, but gains its player a token if they are later eliminated.
+#[derive(Debug)]
+struct Constable;
+
+impl CardEffect for Constable {
+    fn card(&self) -> Card { Card::Constable }
+
+    fn on_eliminated(&self, player_idx: usize) -> Vec<Event> {
+        vec![Event::GainToken { player_idx, card: Card::Constable }]
+    }
+}
+
+/// Compare hands with another player, eliminating whoever holds the higher card.
+#[derive(Debug)]
+struct DowagerQueen;
+
+impl CardEffect for DowagerQueen {
+    fn card(&self) -> Card { Card::DowagerQueen }
+    fn targeting(&self) -> Targeting { Targeting::OTHER }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        context.compare(player_idx, first_target(details)?, Ordering::Greater)
+    }
+}
+
+/// Guess another player's card, gaining a token if the guess is right, after which the target may
+/// discard their hand and draw a new one. Worth 9 at the end of the round, but loses to the
+/// Princess.
+#[derive(Debug)]
+struct Bishop;
+
+impl CardEffect for Bishop {
+    fn card(&self) -> Card { Card::Bishop }
+    fn targeting(&self) -> Targeting { Targeting::OTHER }
+    fn takes_guess(&self) -> bool { true }
+    fn loses_to(&self, other: Card) -> bool { other == Card::Princess }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let target_idx = first_target(details)?;
+        let guess = details.guess().ok_or(GameError::InconsistentState)?;
+        let mut events = vec![Event::Guess { target_idx, guess }];
+        if context.is_holding_value(target_idx, context.value(guess)) {
+            events.push(Event::GainToken { player_idx, card: Card::Bishop });
+            context.require_choice(Card::Bishop, target_idx, ChoiceKind::Redraw);
+        }
+        Ok(events)
+    }
+
+    fn resolve_choice(&self, context: &mut EffectContext, pending: &PendingChoice, choice: Choice) -> Result<Vec<Event>, GameError> {
+        match choice {
+            Choice::Redraw { discard: false } => Ok(Vec::new()),
+            Choice::Redraw { discard: true } => {
+                let mut events = context.discard(pending.player_idx, pending.player_idx)?;
+                if context.is_active(pending.player_idx) {
+                    events.push(context.draw(pending.player_idx)?);
+                }
+                Ok(events)
+            }
+            _ => Err(GameError::InconsistentState),
+        }
+    }
+}
+
+/// Guess another player's card like the Guard. If its player was already insane, a wrong guess
+/// lets them look at the target's hand.
+#[derive(Debug)]
+struct DeepOnes;
+
+impl CardEffect for DeepOnes {
+    fn card(&self) -> Card { Card::DeepOnes }
+    fn targeting(&self) -> Targeting { Targeting::OTHER }
+    fn takes_guess(&self) -> bool { true }
+    fn is_insane(&self) -> bool { true }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let target_idx = first_target(details)?;
+        let mut events = Guard.apply(context, player_idx, details)?;
+        if context.is_active(target_idx) && was_insane(context, player_idx) {
+            events.push(context.show(player_idx, target_idx)?);
+        }
+        Ok(events)
+    }
+}
+
+/// Look at another player's hand like the Priest. If its player was already insane, they look at
+/// the hand of every other player who isn't protected as well.
+#[derive(Debug)]
+struct GoldenMead;
+
+impl CardEffect for GoldenMead {
+    fn card(&self) -> Card { Card::GoldenMead }
+    fn targeting(&self) -> Targeting { Targeting::OTHER }
+    fn is_insane(&self) -> bool { true }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let target_idx = first_target(details)?;
+        let mut events = vec![context.show(player_idx, target_idx)?];
+        if was_insane(context, player_idx) {
+            for other_idx in context.unprotected_others(player_idx).into_iter().filter(|&idx| idx != target_idx) {
+                events.push(context.show(player_idx, other_idx)?);
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// Compare hands with another player like the Baron. If its player was already insane, a target
+/// who is sane is eliminated without comparing.
+#[derive(Debug)]
+struct HoundOfTindalos;
+
+impl CardEffect for HoundOfTindalos {
+    fn card(&self) -> Card { Card::HoundOfTindalos }
+    fn targeting(&self) -> Targeting { Targeting::OTHER }
+    fn is_insane(&self) -> bool { true }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let target_idx = first_target(details)?;
+        if was_insane(context, player_idx) && context.insanity(target_idx) == 0 {
+            context.eliminate(target_idx, EliminationReason::CardEffect { by_idx: player_idx, card: Card::HoundOfTindalos })
+        } else {
+            context.compare(player_idx, target_idx, Ordering::Less)
+        }
+    }
+}
+
+/// Protect the player until their next turn like the Handmaid. If they were already insane, they
+/// also look at the hand of the next player in turn order who isn't protected.
+#[derive(Debug)]
+struct LiberIvonis;
+
+impl CardEffect for LiberIvonis {
+    fn card(&self) -> Card { Card::LiberIvonis }
+    fn is_insane(&self) -> bool { true }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, _details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let mut events = vec![context.protect(player_idx)];
+        if let Some(&next_idx) = context.unprotected_others(player_idx).first().filter(|_| was_insane(context, player_idx)) {
+            events.push(context.show(player_idx, next_idx)?);
+        }
+        Ok(events)
+    }
+}
+
+/// Force any player to discard their hand and draw a new one like the Prince. If its player was
+/// already insane, the target must then make an insanity check.
+#[derive(Debug)]
+struct StarSpawn;
+
+impl CardEffect for StarSpawn {
+    fn card(&self) -> Card { Card::StarSpawn }
+    fn targeting(&self) -> Targeting { Targeting::ANY }
+    fn is_insane(&self) -> bool { true }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let target_idx = first_target(details)?;
+        let mut events = Prince.apply(context, player_idx, details)?;
+        if context.is_active(target_idx) && context.round_winner().is_none() && was_insane(context, player_idx) {
+            events.extend(context.insanity_check(target_idx)?);
+        }
+        Ok(events)
+    }
+}
+
+/// Swap hands with another player like the King. If its player was already insane, they instead
+/// swap hands with every other player who isn't protected, one after another in turn order, so
+/// that each of those players passes their hand to the one before them.
+#[derive(Debug)]
+struct Nyarlathotep;
+
+impl CardEffect for Nyarlathotep {
+    fn card(&self) -> Card { Card::Nyarlathotep }
+    fn targeting(&self) -> Targeting { Targeting::OTHER }
+    fn is_insane(&self) -> bool { true }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        if was_insane(context, player_idx) {
+            context.unprotected_others(player_idx).into_iter().map(|other_idx| context.swap(player_idx, other_idx)).collect()
+        } else {
+            King.apply(context, player_idx, details)
+        }
+    }
+}
+
+/// No effect when played, but must be played rather than the Prince or King, or their insane
+/// versions, like the Countess. If its player was already insane, every other player who isn't
+/// protected must make an insanity check.
+#[derive(Debug)]
+struct MiGo;
+
+impl CardEffect for MiGo {
+    fn card(&self) -> Card { Card::MiGo }
+    fn is_insane(&self) -> bool { true }
+
+    fn restrict(&self, played: Card) -> Result<(), GameError> {
+        match played {
+            Card::Prince | Card::King | Card::StarSpawn | Card::Nyarlathotep => Err(GameError::CannotPlayWhileHoldingMiGo(played)),
+            _ => Ok(()),
+        }
+    }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, _details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        let mut events = Vec::new();
+        if was_insane(context, player_idx) {
+            for other_idx in context.unprotected_others(player_idx) {
+                events.extend(context.insanity_check(other_idx)?);
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// Eliminates its player when played or discarded like the Princess, unless they have already
+/// discarded two other insane cards, in which case they win the round.
+#[derive(Debug)]
+struct Cthulhu;
+
+impl CardEffect for Cthulhu {
+    fn card(&self) -> Card { Card::Cthulhu }
+    fn is_insane(&self) -> bool { true }
+
+    fn apply(&self, context: &mut EffectContext, player_idx: usize, _details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
+        if context.insanity(player_idx) > 2 {
+            context.win(player_idx);
+            Ok(Vec::new())
+        } else {
+            context.eliminate(player_idx, EliminationReason::Played { card: Card::Cthulhu })
+        }
+    }
+
+    fn on_discarded(&self, context: &mut EffectContext, player_idx: usize, by_idx: usize) -> Result<Vec<Event>, GameError> {
+        if context.insanity(player_idx) > 2 {
+            context.win(player_idx);
+            Ok(Vec::new())
+        } else {
+            context.eliminate(player_idx, EliminationReason::Discarded { by_idx, card: Card::Cthulhu })
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! A fixed-length numeric encoding of an `Observation`, for use by learning agents.
+//!
+//! Every observation is encoded as an array of `ENCODED_LENGTH` floats, regardless of the number of
+//! players in the game. Players are listed relative to the observing player - the observer is
+//! always seat 0 of the encoding, the player to their left is seat 1, and so on - and seats beyond
+//! the number of players in the game are padded with zeros.
+//!
+//! The layout is as follows, where `C` is the number of distinct cards and `P` is `MAX_PLAYERS`:
+//!
+//! | Offset               | Length  | Contents                                                     |
+//! |----------------------|---------|--------------------------------------------------------------|
+//! | `HAND_OFFSET`        | `2 * C` | The observer's cards, lowest first, each one-hot by card     |
+//! | `DISCARDS_OFFSET`    | `P * C` | For each seat, the number of each card it has discarded      |
+//! | `PRESENT_OFFSET`     | `P`     | For each seat, 1 if the seat is taking part in the game      |
+//! | `ACTIVE_OFFSET`      | `P`     | For each seat, 1 if the player has not been eliminated       |
+//! | `PROTECTED_OFFSET`   | `P`     | For each seat, 1 if the player is protected by the Handmaid  |
+//! | `DECK_SIZE_OFFSET`   | `1`     | The number of cards left in the deck, divided by `DECK_SIZE` |
+//! | `KNOWN_CARDS_OFFSET` | `P * C` | For each seat, its card one-hot, if known to the observer    |
+//! | `TURN_OFFSET`        | `P`     | One-hot, the seat whose turn it is                           |
+//!
+//! Cards are indexed in the order of `Card::CLASSIC`, since only classic games can be encoded. Any
+//! change to the layout, including adding new cards, must be accompanied by a change to
+//! `ENCODING_VERSION`.
+
+use crate::card::Card;
+use crate::environment::MAX_PLAYERS;
+use crate::observation::Observation;
+use crate::ruleset::Ruleset;
+
+/// The version of the encoding layout, changed whenever the layout changes.
+pub const ENCODING_VERSION: u32 = 1;
+
+/// The number of distinct cards in the classic edition.
+pub const NUM_CARDS: usize = Card::CLASSIC.len();
+
+/// The number of cards in a full deck, used to scale the size of the deck.
+pub const DECK_SIZE: usize = 16;
+
+/// The offset of the observer's hand.
+pub const HAND_OFFSET: usize = 0;
+
+/// The offset of the discard counts.
+pub const DISCARDS_OFFSET: usize = HAND_OFFSET + 2 * NUM_CARDS;
+
+/// The offset of the flags marking which seats are in the game.
+pub const PRESENT_OFFSET: usize = DISCARDS_OFFSET + MAX_PLAYERS * NUM_CARDS;
+
+/// The offset of the flags marking which players have not been eliminated.
+pub const ACTIVE_OFFSET: usize = PRESENT_OFFSET + MAX_PLAYERS;
+
+/// The offset of the flags marking which players are protected.
+pub const PROTECTED_OFFSET: usize = ACTIVE_OFFSET + MAX_PLAYERS;
+
+/// The offset of the size of the deck.
+pub const DECK_SIZE_OFFSET: usize = PROTECTED_OFFSET + MAX_PLAYERS;
+
+/// The offset of the cards known to the observer.
+pub const KNOWN_CARDS_OFFSET: usize = DECK_SIZE_OFFSET + 1;
+
+/// The offset of the seat whose turn it is.
+pub const TURN_OFFSET: usize = KNOWN_CARDS_OFFSET + MAX_PLAYERS * NUM_CARDS;
+
+/// The total length of an encoded observation.
+pub const ENCODED_LENGTH: usize = TURN_OFFSET + MAX_PLAYERS;
+
+/// The index of a card within a block of `NUM_CARDS` entries.
+fn card_index(card: Card) -> usize {
+    Card::CLASSIC.iter().position(|&c| c == card).unwrap()
+}
+
+/// Encode an observation as a fixed-length array of floats, as described in the module
+/// documentation.
+///
+/// # Panics
+///
+/// Panics if the game has more than `MAX_PLAYERS` players, or is not played under the classic
+/// ruleset.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::action::Action;
+/// # use love_letter::encoding::{self, ENCODED_LENGTH, PRESENT_OFFSET, TURN_OFFSET};
+/// # use love_letter::game::Game;
+/// let mut game = Game::new();
+/// game.perform_action(&Action::StartGame { players: 3 }).unwrap();
+///
+/// let encoded = encoding::encode(&game.observation(1));
+/// assert_eq!(encoded.len(), ENCODED_LENGTH);
+/// assert_eq!(&encoded[PRESENT_OFFSET..PRESENT_OFFSET + 4], &[1.0, 1.0, 1.0, 0.0]);
+///
+/// // Player 0 is two seats to the left of player 1 in a three-player game.
+/// assert_eq!(&encoded[TURN_OFFSET..TURN_OFFSET + 4], &[0.0, 0.0, 1.0, 0.0]);
+/// ```
+pub fn encode(observation: &Observation) -> [f32; ENCODED_LENGTH] {
+    assert!(observation.num_players <= MAX_PLAYERS, "too many players to encode");
+    assert!(observation.ruleset == Ruleset::Classic, "only classic games can be encoded");
+
+    let mut encoded = [0.0; ENCODED_LENGTH];
+    let players = observation.num_players;
+    let seat = |player_idx: usize| (player_idx + players - observation.player_idx) % players;
+
+    let mut hand = observation.hand.clone();
+    hand.sort();
+    for (slot, &card) in hand.iter().take(2).enumerate() {
+        encoded[HAND_OFFSET + slot * NUM_CARDS + card_index(card)] = 1.0;
+    }
+
+    for player_idx in 0..players {
+        let seat = seat(player_idx);
+        for card in observation.discards(player_idx) {
+            encoded[DISCARDS_OFFSET + seat * NUM_CARDS + card_index(card)] += 1.0;
+        }
+        encoded[PRESENT_OFFSET + seat] = 1.0;
+        encoded[ACTIVE_OFFSET + seat] = if observation.active[player_idx] { 1.0 } else { 0.0 };
+        encoded[PROTECTED_OFFSET + seat] = if observation.protected[player_idx] { 1.0 } else { 0.0 };
+        if let Some(card) = observation.known_cards[player_idx] {
+            encoded[KNOWN_CARDS_OFFSET + seat * NUM_CARDS + card_index(card)] = 1.0;
+        }
+    }
+
+    encoded[DECK_SIZE_OFFSET] = observation.deck_size as f32 / DECK_SIZE as f32;
+    if let Some(current_player) = observation.current_player {
+        encoded[TURN_OFFSET + seat(current_player)] = 1.0;
+    }
+
+    encoded
+}
@@ -0,0 +1,309 @@
+//! A reinforcement-learning environment built around the game engine.
+//!
+//! The environment follows the conventions of OpenAI Gym: an episode is a single game, begun with
+//! `reset` and advanced with `step`, which takes an action identified by an integer from a fixed
+//! discrete action space. One seat at the table is controlled by the learner, and every other seat
+//! is filled by an `Agent`, whose moves are made automatically between the learner's turns.
+//!
+//! The action space enumerates every combination of card, target and guess that could ever be
+//! played in a classic game, whether or not it is legal at a given moment. Use `legal_action_mask`
+//! to find out which actions are currently allowed.
+
+use std::error::Error;
+use std::fmt;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::action::{Action, PlayCardDetails};
+use crate::agent::{Agent, AgentError, AgentFactory};
+use crate::card::Card;
+use crate::event::Event;
+use crate::game::{Game, GameError};
+use crate::observation::Observation;
+
+/// The largest number of players supported by the action space.
+pub const MAX_PLAYERS: usize = 4;
+
+/// Every play in the discrete action space, in order of action identifier.
+///
+/// The action space consists of, in order: a Guard with no target, followed by a Guard against
+/// each seat with each guess other than Guard; then, for each other card in ascending order, the
+/// card with no target (if it can be played without one) followed by the card against each seat
+/// (if it has a target).
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::action::PlayCardDetails;
+/// # use love_letter::card::Card;
+/// # use love_letter::environment;
+/// let space = environment::action_space();
+/// assert_eq!(space.len(), 51);
+/// assert_eq!(space[0], PlayCardDetails::PlayGuard { target_idx: None, guess: Card::Guard });
+/// assert_eq!(space[1], PlayCardDetails::PlayGuard { target_idx: Some(0), guess: Card::Priest });
+/// ```
+pub fn action_space() -> Vec<PlayCardDetails> {
+    let mut space = Vec::new();
+    for &card in Card::CLASSIC.iter() {
+        space.extend(PlayCardDetails::from_parts(card, None, None));
+        if card.has_target() {
+            for target_idx in 0..MAX_PLAYERS {
+                if card == Card::Guard {
+                    space.extend(Card::CLASSIC.iter()
+                        .filter(|&&guess| guess != Card::Guard)
+                        .filter_map(|&guess| PlayCardDetails::from_parts(card, Some(target_idx), Some(guess))));
+                } else {
+                    space.extend(PlayCardDetails::from_parts(card, Some(target_idx), None));
+                }
+            }
+        }
+    }
+    space
+}
+
+/// An error arising from misuse of an `Environment`.
+#[derive(Clone, Debug)]
+pub enum EnvironmentError {
+
+    /// The action identifier is outside of the action space.
+    UnknownAction(usize),
+
+    /// `step` was called before `reset`, or after the episode finished.
+    EpisodeNotInProgress,
+
+    /// The game engine rejected the action.
+    Game(GameError),
+
+    /// The agent for one of the other seats could not be created.
+    Agent(AgentError),
+}
+
+impl fmt::Display for EnvironmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use EnvironmentError::*;
+        match self {
+            UnknownAction(action_id) => write!(f, "Action {} is not in the action space.", action_id),
+            EpisodeNotInProgress => write!(f, "No episode is in progress."),
+            Game(err) => write!(f, "{}", err),
+            Agent(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for EnvironmentError {}
+
+impl From<GameError> for EnvironmentError {
+    fn from(err: GameError) -> Self {
+        Self::Game(err)
+    }
+}
+
+impl From<AgentError> for EnvironmentError {
+    fn from(err: AgentError) -> Self {
+        Self::Agent(err)
+    }
+}
+
+/// Additional information about a step, not needed for learning but useful for debugging.
+#[derive(Clone, PartialEq, Debug)]
+pub struct StepInfo {
+
+    /// The events visible to the learner since the previous step (or since the episode began),
+    /// including the moves made by the other seats.
+    pub events: Vec<Event>,
+
+    /// The winners of the game, if it has finished.
+    pub winners: Vec<usize>,
+}
+
+/// The result of taking a step in the environment.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Step {
+
+    /// The learner's view of the game after the step.
+    pub observation: Observation,
+
+    /// The reward for the step: the learner's share of the win when the game ends in their
+    /// favour, and 0 otherwise.
+    pub reward: f64,
+
+    /// Whether the game has finished.
+    pub done: bool,
+
+    /// Additional information about the step.
+    pub info: StepInfo,
+}
+
+/// A Gym-style environment in which a learner plays one seat of a game against other agents.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::agent;
+/// # use love_letter::environment::Environment;
+/// let opponents = vec![agent::factory_by_name("random").unwrap(); 2];
+/// let mut env = Environment::new(1, opponents);
+///
+/// let mut step = env.reset(7).unwrap();
+/// while !step.done {
+///     let action_id = env.legal_action_mask().iter().position(|&legal| legal).unwrap();
+///     step = env.step(action_id).unwrap();
+/// }
+/// assert!(step.reward == 0.0 || step.info.winners.contains(&1));
+/// ```
+pub struct Environment {
+    learner_seat: usize,
+    opponent_factories: Vec<AgentFactory>,
+    agents: Vec<Option<Box<dyn Agent>>>,
+    actions: Vec<PlayCardDetails>,
+    game: Game,
+    pending_events: Vec<Event>,
+}
+
+impl Environment {
+
+    /// Create an environment in which the learner occupies the given seat, and the remaining
+    /// seats are filled, in order, by agents created from the given factories.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resulting number of players is more than `MAX_PLAYERS`, or if the learner's
+    /// seat doesn't exist.
+    pub fn new(learner_seat: usize, opponents: Vec<AgentFactory>) -> Self {
+        assert!(opponents.len() < MAX_PLAYERS, "too many players for the action space");
+        assert!(learner_seat <= opponents.len(), "learner seat does not exist");
+        Self {
+            learner_seat,
+            opponent_factories: opponents,
+            agents: Vec::new(),
+            actions: action_space(),
+            game: Game::new(),
+            pending_events: Vec::new(),
+        }
+    }
+
+    /// The number of actions in the action space.
+    pub fn num_actions(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// The play corresponding to the given action identifier.
+    pub fn action(&self, action_id: usize) -> Option<&PlayCardDetails> {
+        self.actions.get(action_id)
+    }
+
+    /// The action identifier corresponding to the given play.
+    pub fn action_id(&self, details: &PlayCardDetails) -> Option<usize> {
+        self.actions.iter().position(|action| action == details)
+    }
+
+    /// Which actions in the action space the learner may currently take.
+    pub fn legal_action_mask(&self) -> Vec<bool> {
+        let legal_plays = self.game.legal_plays(self.learner_seat);
+        self.actions.iter().map(|action| legal_plays.contains(action)).collect()
+    }
+
+    /// Begin a new episode, with all randomness determined by the given seed, and return the
+    /// learner's first observation along with the events before their first turn.
+    ///
+    /// The other seats may end the game before the learner has taken a turn, in which case the
+    /// step returned is the last of the episode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::agent;
+    /// # use love_letter::environment::{Environment, EnvironmentError};
+    /// # use love_letter::event::Event;
+    /// let mut env = Environment::new(1, vec![agent::factory_by_name("random").unwrap()]);
+    ///
+    /// // Find a game in which the learner is knocked out before their first turn.
+    /// let step = (0..).map(|seed| env.reset(seed).unwrap())
+    ///     .find(|step| step.done && step.info.winners == [0])
+    ///     .unwrap();
+    /// assert_eq!(step.reward, 0.0);
+    /// assert!(matches!(step.info.events.last(), Some(Event::GameOver { .. })));
+    /// assert!(matches!(env.step(0), Err(EnvironmentError::EpisodeNotInProgress)));
+    /// ```
+    pub fn reset(&mut self, seed: u64) -> Result<Step, EnvironmentError> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let players = self.opponent_factories.len() + 1;
+
+        let mut factories = self.opponent_factories.iter();
+        self.agents = (0..players).map(|seat| {
+            if seat == self.learner_seat { None } else { Some(factories.next().unwrap()(rng.gen())) }
+        }).map(Option::transpose).collect::<Result<_, _>>()?;
+        for (seat, agent) in self.agents.iter_mut().enumerate() {
+            if let Some(agent) = agent {
+                agent.new_game(players, seat);
+            }
+        }
+
+        self.game = Game::with_seed(rng.gen());
+        self.pending_events.clear();
+        let events = self.game.perform_action(&Action::StartGame { players })?;
+        self.dispatch(events);
+        self.play_opponents()?;
+
+        Ok(self.take_step())
+    }
+
+    /// Take the given action on behalf of the learner, and advance the game until it is the
+    /// learner's turn again or the game ends.
+    pub fn step(&mut self, action_id: usize) -> Result<Step, EnvironmentError> {
+        let details = self.action(action_id).cloned().ok_or(EnvironmentError::UnknownAction(action_id))?;
+        if self.game.current_player() != Some(self.learner_seat) {
+            return Err(EnvironmentError::EpisodeNotInProgress);
+        }
+
+        let events = self.game.perform_action(&Action::PlayCard { player_idx: self.learner_seat, details })?;
+        self.dispatch(events);
+        self.play_opponents()?;
+
+        Ok(self.take_step())
+    }
+
+    /// Gather up the learner's view of the game and the events since the previous step.
+    fn take_step(&mut self) -> Step {
+        let events = self.pending_events.drain(..).collect::<Vec<_>>();
+        let winners = match events.last() {
+            Some(Event::GameOver { winner_indices }) => winner_indices.clone(),
+            _ => Vec::new(),
+        };
+        let reward = if winners.contains(&self.learner_seat) { 1.0 / winners.len() as f64 } else { 0.0 };
+
+        Step {
+            observation: self.game.observation(self.learner_seat),
+            reward,
+            done: self.game.is_over(),
+            info: StepInfo { events, winners },
+        }
+    }
+
+    /// Let the other seats play until it is the learner's turn or the game ends.
+    fn play_opponents(&mut self) -> Result<(), GameError> {
+        while let Some(player_idx) = self.game.current_player().filter(|&idx| idx != self.learner_seat) {
+            let observation = self.game.observation(player_idx);
+            let legal_plays = self.game.legal_plays(player_idx);
+            let details = self.agents[player_idx].as_mut().unwrap().choose_play(&observation, &legal_plays);
+            let events = self.game.perform_action(&Action::PlayCard { player_idx, details })?;
+            self.dispatch(events);
+        }
+        Ok(())
+    }
+
+    /// Inform every seat of the events that they are entitled to see.
+    fn dispatch(&mut self, events: Vec<Event>) {
+        for event in events {
+            for (seat, agent) in self.agents.iter_mut().enumerate() {
+                if let Some(agent) = agent.as_mut().filter(|_| event.is_visible_to(seat)) {
+                    agent.notify(&event);
+                }
+            }
+            if event.is_visible_to(self.learner_seat) {
+                self.pending_events.push(event);
+            }
+        }
+    }
+}
@@ -16,12 +16,14 @@
 //!   - Game starts
 //!   - Players join the game
 //!   - It is player X's turn
+//!   - Player X's turn is over
 //!   - Game ends
 
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
 use crate::card::Card;
+use crate::choice::ChoiceKind;
 
 /// An event that happens as a result of an action in a game of Love Letter.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -46,9 +48,15 @@ pub enum Event {
     /// One of the players needs to play a card.
     ReadyToPlay { player_idx: usize },
 
+    /// A player's turn is over, once the card they played has taken effect.
+    TurnEnded { player_idx: usize },
+
     /// One of the players plays a card from their hand.
     PlayCard { player_idx: usize, card: Card },
 
+    /// A card that needs a target is played when nobody can be targeted, and so has no effect.
+    NoValidTarget { player_idx: usize, card: Card },
+
     /// One of the players has a guess made about their card.
     Guess { target_idx: usize, guess: Card },
 
@@ -64,12 +72,123 @@ pub enum Event {
     /// Two players swap hands.
     SwapHands { player_idx: usize, player_card: Card, target_idx: usize, target_card: Card },
 
-    /// A player is eliminated from the game.
-    EliminatePlayer { player_idx: usize },
+    /// A player is eliminated from the game, for the given reason.
+    EliminatePlayer { player_idx: usize, reason: EliminationReason },
 
     /// One of the players reveals their card after being eliminated.
     RevealCard { player_idx: usize, card: Card },
 
+    /// A player is protected from other players' cards until the start of their next turn.
+    ProtectionGranted { player_idx: usize },
+
+    /// A player's protection ends at the start of their turn.
+    ProtectionExpired { player_idx: usize },
+
+    /// A player reveals a card from the top of the deck as part of an insanity check in Lovecraft
+    /// Letter, and adds it to their discards.
+    InsanityCheck { player_idx: usize, card: Card },
+
+    /// A player chooses a target for a card whose effect comes later, such as the Jester or the
+    /// Sycophant.
+    ChooseTarget { player_idx: usize, target_idx: usize },
+
+    /// A player must make a choice before the card being played can finish taking effect.
+    ChoiceRequired { player_idx: usize, kind: ChoiceKind },
+
+    /// A player still in the game at the end of the round reveals their hand. Their score is the
+    /// value of their card plus any bonus from their discards, such as the Count, and ties on score
+    /// are broken by the total value of their discards.
+    FinalHand { player_idx: usize, card: Card, score: u32, discard_total: u32 },
+
+    /// The winners of the round have been decided, for the given reason.
+    WinnersDecided { reason: WinReason },
+
+    /// A player gains a token of affection through the effect of a card, such as the Jester,
+    /// Constable or Bishop, without necessarily winning the round.
+    GainToken { player_idx: usize, card: Card },
+
     /// The game ends and the winners are announced.
     GameOver { winner_indices: Vec<usize> },
 }
+
+/// Why a player was eliminated from the game.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum EliminationReason {
+
+    /// Their card was guessed by the given player, with a card such as the Guard.
+    Guessed { by_idx: usize },
+
+    /// They held the weaker card when comparing hands with the given player, with a card such as
+    /// the Baron.
+    LostComparison { by_idx: usize },
+
+    /// They were made to discard a card such as the Princess by the given player with a card such
+    /// as the Prince, or chose to discard it themselves, as when redrawing after the Bishop.
+    Discarded { by_idx: usize, card: Card },
+
+    /// They played a card such as the Princess.
+    Played { card: Card },
+
+    /// They revealed the given insane card in an insanity check.
+    InsanityCheck { card: Card },
+
+    /// The effect of a card played or held by the given player, such as the Assassin.
+    CardEffect { by_idx: usize, card: Card },
+
+    /// They ran out of time to make their move.
+    Timeout,
+
+    /// They forfeited the game.
+    Forfeit,
+}
+
+/// How the winners of a round were decided.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum WinReason {
+
+    /// Every other player was eliminated. If a custom card eliminated everybody, nobody wins.
+    LastPlayerStanding,
+
+    /// A card such as Cthulhu won the round outright.
+    CardEffect,
+
+    /// The deck ran out, and the winner had the highest score. A card which loses to another card
+    /// still held, as the Bishop does to the Princess, cannot win however high its score.
+    HighestScore,
+
+    /// The deck ran out with several players sharing the highest score, and the winner had
+    /// discarded the highest total value of cards.
+    DiscardTotal,
+
+    /// The deck ran out with several players sharing both the highest score and the highest total
+    /// value of discards, and they all win.
+    Tie,
+}
+
+impl Event {
+
+    /// Returns whether the given player is entitled to see this event. Events that reveal the
+    /// contents of a player's hand are only visible to the players involved; everything else is
+    /// public.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::card::Card;
+    /// # use love_letter::event::Event;
+    /// let event = Event::DealCard { player_idx: 1, card: Card::King };
+    /// assert!(event.is_visible_to(1));
+    /// assert!(!event.is_visible_to(0));
+    /// ```
+    pub fn is_visible_to(&self, seat: usize) -> bool {
+        match self {
+            Self::DealCard { player_idx, .. } => *player_idx == seat,
+            Self::ShowCard { player_idx, .. } => *player_idx == seat,
+            Self::CompareHands { player_idx, target_idx, .. } => *player_idx == seat || *target_idx == seat,
+            Self::SwapHands { player_idx, target_idx, .. } => *player_idx == seat || *target_idx == seat,
+            _ => true,
+        }
+    }
+}
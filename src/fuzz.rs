@@ -0,0 +1,243 @@
+//! A harness for checking that no sequence of actions can make the engine panic or hang.
+//!
+//! Every action given to `Game::perform_action` should either be carried out or rejected with a
+//! `GameError`, however nonsensical it is. The harness checks this by playing many games made up
+//! of arbitrary actions: a mixture of legal plays, which let the games progress into interesting
+//! positions, and plays, choices, forfeits, timeouts and requests to start new games with random,
+//! and often invalid, players, cards, targets and guesses. Half of the games ask players to make
+//! their own choices.
+//!
+//! The invariants described by `Game::check_invariants` are checked after every action, and any
+//! violation is treated as a panic. Each game is played on its own thread so that panics can be
+//! caught and hangs detected. Every game is determined by a single seed, so a failure can be
+//! reproduced with `run_game`.
+
+use std::fmt;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::action::{Action, PlayCardDetails};
+use crate::card::Card;
+use crate::choice::Choice;
+use crate::custom::{CustomCard, CustomDeck, Primitive};
+use crate::game::Game;
+use crate::ruleset::Ruleset;
+
+/// The number of actions taken in each game.
+pub const ACTIONS_PER_GAME: usize = 200;
+
+/// How long a single action may take before the engine is considered to have hung.
+pub const ACTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The largest player index, and number of players, used in arbitrary actions.
+const MAX_PLAYER_IDX: usize = 9;
+
+/// The number of distinct cards in an arbitrary custom deck is less than this.
+const MAX_CUSTOM_CARDS: usize = 6;
+
+/// Every primitive out of which a custom card's effect can be made.
+const PRIMITIVES: [Primitive; 7] = [
+    Primitive::Guess, Primitive::Compare, Primitive::Peek, Primitive::Swap,
+    Primitive::ForceDiscard, Primitive::Protect, Primitive::EliminateSelfIfDiscarded,
+];
+
+/// What went wrong during a failed game.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum FuzzFailureKind {
+
+    /// The engine panicked with the given message.
+    Panic(String),
+
+    /// The engine did not finish an action within `ACTION_TIMEOUT`.
+    Hang,
+}
+
+/// A game during which the engine misbehaved.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FuzzFailure {
+
+    /// The seed from which the game can be reproduced using `run_game`.
+    pub game_seed: u64,
+
+    /// The actions taken in the game, up to and including the one that failed.
+    pub actions: Vec<Action>,
+
+    /// What went wrong.
+    pub kind: FuzzFailureKind,
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            FuzzFailureKind::Panic(message) => writeln!(f, "Game {} panicked: {}", self.game_seed, message)?,
+            FuzzFailureKind::Hang => writeln!(f, "Game {} hung", self.game_seed)?,
+        }
+        for action in &self.actions {
+            writeln!(f, "  {:?}", action)?;
+        }
+        Ok(())
+    }
+}
+
+/// Choose an arbitrary action to take in the given game. About half of the time this is a legal
+/// play or choice, if there is one.
+pub fn arbitrary_action<R: Rng + ?Sized>(game: &Game, rng: &mut R) -> Action {
+    if let Some(pending) = game.pending_choice().filter(|_| rng.gen_bool(0.5)) {
+        let player_idx = pending.player_idx;
+        let choice = *game.legal_choices(player_idx).choose(rng).unwrap();
+        return Action::MakeChoice { player_idx, choice };
+    }
+    if let Some(player_idx) = game.current_player().filter(|_| rng.gen_bool(0.5)) {
+        if let Some(details) = game.legal_plays(player_idx).choose(rng) {
+            return Action::PlayCard { player_idx, details: details.clone() };
+        }
+    }
+
+    if rng.gen_bool(0.05) {
+        return Action::StartGame { players: rng.gen_range(0, MAX_PLAYER_IDX + 1) };
+    }
+    if rng.gen_bool(0.02) {
+        return Action::Forfeit { player_idx: rng.gen_range(0, MAX_PLAYER_IDX + 1) };
+    }
+    if rng.gen_bool(0.02) {
+        let player_idx = game.current_player().or_else(|| game.pending_choice().map(|pending| pending.player_idx));
+        return Action::Timeout { player_idx: player_idx.unwrap_or_else(|| rng.gen_range(0, MAX_PLAYER_IDX + 1)) };
+    }
+    if rng.gen_bool(0.1) {
+        return Action::MakeChoice { player_idx: rng.gen_range(0, MAX_PLAYER_IDX + 1), choice: arbitrary_choice(rng) };
+    }
+
+    let player_idx = match game.current_player() {
+        Some(player_idx) if rng.gen_bool(0.75) => player_idx,
+        _ => rng.gen_range(0, MAX_PLAYER_IDX + 1),
+    };
+    Action::PlayCard { player_idx, details: arbitrary_details(rng) }
+}
+
+/// Choose an arbitrary choice, which need not make any sense.
+fn arbitrary_choice<R: Rng + ?Sized>(rng: &mut R) -> Choice {
+    if rng.gen_bool(0.5) {
+        Choice::LookAtHand { target_idx: rng.gen_range(0, MAX_PLAYER_IDX + 1) }
+    } else {
+        Choice::Redraw { discard: rng.gen() }
+    }
+}
+
+/// Choose an arbitrary play, which need not make any sense.
+fn arbitrary_details<R: Rng + ?Sized>(rng: &mut R) -> PlayCardDetails {
+    use PlayCardDetails::*;
+    let target_idx = if rng.gen_bool(0.2) { None } else { Some(rng.gen_range(0, MAX_PLAYER_IDX + 1)) };
+    let second_target_idx = if rng.gen_bool(0.4) { None } else { Some(rng.gen_range(0, MAX_PLAYER_IDX + 1)) };
+    let card = arbitrary_card(rng);
+    match arbitrary_card(rng) {
+        Card::Guard => PlayGuard { target_idx, guess: card },
+        Card::Priest => PlayPriest { target_idx },
+        Card::Baron => PlayBaron { target_idx },
+        Card::Handmaid => PlayHandmaid {},
+        Card::Prince => PlayPrince { target_idx: target_idx.unwrap_or(0) },
+        Card::King => PlayKing { target_idx },
+        Card::Countess => PlayCountess {},
+        Card::Princess => PlayPrincess {},
+        Card::Jester => PlayJester { target_idx },
+        Card::Assassin => PlayAssassin {},
+        Card::Cardinal => PlayCardinal { target_idx, second_target_idx },
+        Card::Baroness => PlayBaroness { target_idx, second_target_idx },
+        Card::Sycophant => PlaySycophant { target_idx },
+        Card::Count => PlayCount {},
+        Card::Constable => PlayConstable {},
+        Card::DowagerQueen => PlayDowagerQueen { target_idx },
+        Card::Bishop => PlayBishop { target_idx, guess: card },
+        Card::DeepOnes => PlayDeepOnes { target_idx, guess: card },
+        Card::GoldenMead => PlayGoldenMead { target_idx },
+        Card::HoundOfTindalos => PlayHoundOfTindalos { target_idx },
+        Card::LiberIvonis => PlayLiberIvonis {},
+        Card::StarSpawn => PlayStarSpawn { target_idx: target_idx.unwrap_or(0) },
+        Card::Nyarlathotep => PlayNyarlathotep { target_idx },
+        Card::MiGo => PlayMiGo {},
+        Card::Cthulhu => PlayCthulhu {},
+        Card::Custom(id) => PlayCustom { id, target_idx, guess: Some(card).filter(|_| rng.gen_bool(0.8)) },
+    }
+}
+
+/// Choose an arbitrary card, which is sometimes a custom card.
+fn arbitrary_card<R: Rng + ?Sized>(rng: &mut R) -> Card {
+    if rng.gen_bool(0.2) {
+        Card::Custom(rng.gen_range(0, MAX_CUSTOM_CARDS as u8))
+    } else {
+        *Card::ALL.choose(rng).unwrap()
+    }
+}
+
+/// Choose an arbitrary custom deck, which need not be valid.
+fn arbitrary_deck<R: Rng + ?Sized>(rng: &mut R) -> CustomDeck {
+    let cards = (0..rng.gen_range(0, MAX_CUSTOM_CARDS)).map(|id| CustomCard {
+        name: format!("Card {}", rng.gen_range(0, id + 2)),
+        rank: rng.gen_range(0, 10),
+        copies: rng.gen_range(0, 8),
+        effect: (0..rng.gen_range(0, 4)).map(|_| *PRIMITIVES.choose(rng).unwrap()).collect(),
+    }).collect();
+    CustomDeck { cards }
+}
+
+/// Play a single game of arbitrary actions under a ruleset, sometimes with a custom deck and
+/// sometimes asking for choices, determined by the given seed, passing each action to `record`
+/// before it is taken.
+pub fn run_game<F: FnMut(&Action)>(game_seed: u64, mut record: F) {
+    let mut rng = StdRng::seed_from_u64(game_seed);
+    let ruleset = *Ruleset::ALL.choose(&mut rng).unwrap();
+    let mut game = Game::with_seed(rng.gen()).with_ruleset(ruleset);
+    if rng.gen_bool(0.25) {
+        game = game.with_deck(arbitrary_deck(&mut rng));
+    }
+    if rng.gen_bool(0.5) {
+        game = game.with_choices();
+    }
+    for _ in 0..ACTIONS_PER_GAME {
+        let action = arbitrary_action(&game, &mut rng);
+        record(&action);
+        let _ = game.perform_action(&action);
+        if let Err(violation) = game.check_invariants() {
+            panic!("{}", violation);
+        }
+    }
+}
+
+/// Play the given number of games of arbitrary actions, with seeds derived from the given seed,
+/// stopping at the first game in which the engine panics or hangs.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::fuzz;
+/// assert_eq!(fuzz::run(0, 50), Ok(()));
+/// ```
+pub fn run(seed: u64, games: usize) -> Result<(), FuzzFailure> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..games {
+        let game_seed = rng.gen();
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn(move || run_game(game_seed, |action| { let _ = sender.send(action.clone()); }));
+
+        let mut actions = Vec::new();
+        loop {
+            match receiver.recv_timeout(ACTION_TIMEOUT) {
+                Ok(action) => actions.push(action),
+                Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => return Err(FuzzFailure { game_seed, actions, kind: FuzzFailureKind::Hang }),
+            }
+        }
+
+        if let Err(payload) = handle.join() {
+            let message = payload.downcast_ref::<&str>().map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            return Err(FuzzFailure { game_seed, actions, kind: FuzzFailureKind::Panic(message) });
+        }
+    }
+    Ok(())
+}
@@ -11,17 +11,37 @@
 
 use std::error::Error;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
 use crate::action::{Action, PlayCardDetails};
 use crate::card::Card;
+use crate::choice::{Choice, ChoiceKind, PendingChoice};
+use crate::custom::{CustomDeck, DeckError};
 use crate::deck::Deck;
-use crate::event::Event;
+use crate::effect::{CardEffect, EffectContext, EffectRegistry, Mark};
+use crate::event::{EliminationReason, Event, WinReason};
+use crate::hashing::{self, Feature, InformationSet};
+use crate::history::DiscardKind;
+use crate::observation::Observation;
 use crate::player::Player;
+use crate::ruleset::Ruleset;
+
+/// The number of random deals tried by `Game::sample_determinization` before giving up on finding
+/// one consistent with the player's observations.
+pub const MAX_SAMPLING_ATTEMPTS: usize = 10_000;
 
 /// An engine capable of playing a whole game of Love Letter.
+///
+/// Two games compare equal if they are in the same position, regardless of the order in which
+/// cards were discarded or are held, and regardless of the state of their random number generators.
 #[derive(Clone, Debug)]
 pub struct Game {
 
@@ -37,8 +57,50 @@ pub struct Game {
     /// The player whose turn it is to play.
     turn_counter: usize,
 
+    /// What each player knows about the cards held by the others, as a result of the Priest,
+    /// Baron and King. `knowledge[a][b]` is the card that player `a` knows player `b` to hold.
+    knowledge: Vec<Vec<Option<Card>>>,
+
+    /// The cards that every player can deduce each player is not holding, as a result of missed
+    /// Guard guesses, Baron comparisons and the restriction on playing with the Countess.
+    exclusions: Vec<Vec<Card>>,
+
+    /// The cards publicly removed from the game at the start of a two-player game.
+    removed_cards: Vec<Card>,
+
+    /// The rules under which games are played.
+    ruleset: Ruleset,
+
+    /// The custom deck used in place of the ruleset's cards, if any.
+    custom_deck: Option<Arc<CustomDeck>>,
+
+    /// The effect of each card.
+    effects: Arc<EffectRegistry>,
+
+    /// The player who must be targeted by the next card played if possible, having been chosen
+    /// with a card such as the Sycophant.
+    forced_target: Option<usize>,
+
+    /// The choices made this round with cards whose effect comes at the end of the round, such as
+    /// the Jester.
+    marks: Vec<Mark>,
+
+    /// The player who has won the round outright through the card being played, such as Cthulhu,
+    /// before the round has been brought to an end.
+    round_winner: Option<usize>,
+
+    /// Whether players are asked to make the choices that some cards call for, rather than having
+    /// them made on their behalf.
+    asks_for_choices: bool,
+
+    /// The choice which the card being played is waiting for, if any.
+    pending_choice: Option<PendingChoice>,
+
     /// The high-level state of the game.
     state: GameState,
+
+    /// The source of randomness used to shuffle the deck.
+    rng: StdRng,
 }
 
 impl Game {
@@ -52,21 +114,615 @@ impl Game {
     /// let game = Game::new();
     /// ```
     pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    /// Create a new game which has not yet started, whose deck will be shuffled deterministically
+    /// according to the given seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::Action;
+    /// # use love_letter::game::Game;
+    /// let mut first = Game::with_seed(42);
+    /// let mut second = Game::with_seed(42);
+    /// let action = Action::StartGame { players: 2 };
+    /// assert_eq!(first.perform_action(&action).unwrap(), second.perform_action(&action).unwrap());
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    /// Create a new game which has not yet started, using the given source of randomness.
+    fn with_rng(rng: StdRng) -> Self {
         Game {
             deck: Deck::new(),
             burned_card: None,
             players: Vec::new(),
             turn_counter: 0,
+            knowledge: Vec::new(),
+            exclusions: Vec::new(),
+            removed_cards: Vec::new(),
+            ruleset: Ruleset::Classic,
+            custom_deck: None,
+            effects: Arc::new(EffectRegistry::new()),
+            forced_target: None,
+            marks: Vec::new(),
+            round_winner: None,
+            asks_for_choices: false,
+            pending_choice: None,
             state: GameState::NotStarted,
+            rng,
+        }
+    }
+
+    /// Create a game in progress from the given position, in which the given player is to play.
+    /// Nobody knows anything about anybody else's hand. The position is not checked for validity.
+    pub(crate) fn from_position(ruleset: Ruleset, players: Vec<Player>, deck: Deck, burned_card: Option<Card>, removed_cards: Vec<Card>, turn_counter: usize, rng: StdRng) -> Self {
+        let num_players = players.len();
+        Game {
+            deck,
+            burned_card,
+            players,
+            turn_counter,
+            knowledge: vec![vec![None; num_players]; num_players],
+            exclusions: vec![Vec::new(); num_players],
+            removed_cards,
+            ruleset,
+            custom_deck: None,
+            effects: Arc::new(EffectRegistry::new()),
+            forced_target: None,
+            marks: Vec::new(),
+            round_winner: None,
+            asks_for_choices: false,
+            pending_choice: None,
+            state: GameState::InProgress,
+            rng,
+        }
+    }
+
+    /// Use the given ruleset for every game started from now on. This should be called before the
+    /// first game is started, since changing the cards in the deck part of the way through a game
+    /// leaves it in an inconsistent state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::Action;
+    /// # use love_letter::game::{Game, GameError};
+    /// # use love_letter::ruleset::Ruleset;
+    /// let mut game = Game::new();
+    /// assert_eq!(game.perform_action(&Action::StartGame { players: 8 }), Err(GameError::InvalidNumberOfPlayers(8)));
+    ///
+    /// let mut game = Game::new().with_ruleset(Ruleset::Premium);
+    /// assert!(game.perform_action(&Action::StartGame { players: 8 }).is_ok());
+    /// ```
+    pub fn with_ruleset(mut self, ruleset: Ruleset) -> Self {
+        self.ruleset = ruleset;
+        self
+    }
+
+    /// Get the rules under which games are played.
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+
+    /// Replace the standard effect of a card with the given one, for playing with house rules.
+    /// Like the ruleset, this should be set before the first game is started.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::{Action, PlayCardDetails};
+    /// # use love_letter::card::Card;
+    /// # use love_letter::effect::CardEffect;
+    /// # use love_letter::game::{Game, GameError};
+    /// // A house rule in which the Countess may be played like any other card.
+    /// #[derive(Debug)]
+    /// struct RelaxedCountess;
+    ///
+    /// impl CardEffect for RelaxedCountess {
+    ///     fn card(&self) -> Card {
+    ///         Card::Countess
+    ///     }
+    /// }
+    ///
+    /// let game = Game::new().with_effect(RelaxedCountess);
+    /// assert_eq!(game.effect(Card::Countess).restrict(Card::King), Ok(()));
+    /// assert_eq!(Game::new().effect(Card::Countess).restrict(Card::King), Err(GameError::CannotPlayWhileHoldingCountess(Card::King)));
+    /// ```
+    pub fn with_effect<E: CardEffect + 'static>(mut self, effect: E) -> Self {
+        Arc::make_mut(&mut self.effects).register(effect);
+        self
+    }
+
+    /// Play every game started from now on with the given custom deck, rather than the cards of the
+    /// ruleset. The ruleset still determines the number of players allowed. The deck is checked
+    /// when each game is started, and a game cannot be started with a deck that is invalid or has
+    /// too few cards for the players.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::Action;
+    /// # use love_letter::card::Card;
+    /// # use love_letter::custom::{CustomCard, CustomDeck, DeckError, Primitive};
+    /// # use love_letter::game::{Game, GameError};
+    /// let card = |name: &str, rank, copies, effect| CustomCard { name: name.to_string(), rank, copies, effect };
+    /// let deck = CustomDeck { cards: vec![
+    ///     card("Spy", 1, 5, vec![Primitive::Guess]),
+    ///     card("Informant", 2, 2, vec![Primitive::Peek, Primitive::Protect]),
+    ///     card("Heir", 5, 1, vec![Primitive::EliminateSelfIfDiscarded]),
+    /// ] };
+    ///
+    /// let mut game = Game::new().with_deck(deck.clone());
+    /// game.perform_action(&Action::StartGame { players: 3 }).unwrap();
+    /// assert_eq!(game.effect(Card::Custom(1)).name(), "Informant");
+    /// while let Some(player_idx) = game.current_player() {
+    ///     let details = game.legal_plays(player_idx)[0].clone();
+    ///     game.perform_action(&Action::PlayCard { player_idx, details }).unwrap();
+    /// }
+    ///
+    /// let mut game = Game::new().with_deck(CustomDeck { cards: deck.cards[1..].to_vec() });
+    /// let err = game.perform_action(&Action::StartGame { players: 3 }).unwrap_err();
+    /// assert_eq!(err, GameError::InvalidDeck(DeckError::NotEnoughCards { required: 5, available: 3 }));
+    /// ```
+    pub fn with_deck(mut self, deck: CustomDeck) -> Self {
+        deck.register_effects(Arc::make_mut(&mut self.effects));
+        self.custom_deck = Some(Arc::new(deck));
+        self
+    }
+
+    /// Ask players to make the choices that some cards call for part of the way through their
+    /// effect, such as which hand to look at after playing the Cardinal. When a choice is needed,
+    /// the play stops with `Event::ChoiceRequired`, and nothing else can happen until the player
+    /// answers with `Action::MakeChoice`. Otherwise, each choice is made on the player's behalf as
+    /// described by `ChoiceKind::default_choice`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::{Action, PlayCardDetails};
+    /// # use love_letter::builder::GameBuilder;
+    /// # use love_letter::card::Card;
+    /// # use love_letter::choice::{Choice, ChoiceKind};
+    /// # use love_letter::event::Event;
+    /// # use love_letter::game::GameError;
+    /// # use love_letter::ruleset::Ruleset;
+    /// use Card::*;
+    ///
+    /// let mut game = GameBuilder::new(3)
+    ///     .ruleset(Ruleset::Premium)
+    ///     .hand(0, &[Cardinal, Guard])
+    ///     .hand(1, &[King])
+    ///     .hand(2, &[Baron])
+    ///     .deck(&[Jester, Assassin, Guard, Guard, Guard, Guard, Guard, Guard, Guard, Priest, Priest, Cardinal,
+    ///         Baron, Baroness, Baroness, Handmaid, Handmaid, Sycophant, Sycophant, Prince, Prince, Count,
+    ///         Count, Constable, Countess, DowagerQueen, Princess, Bishop])
+    ///     .build()
+    ///     .unwrap()
+    ///     .with_choices();
+    ///
+    /// let details = PlayCardDetails::PlayCardinal { target_idx: Some(1), second_target_idx: Some(2) };
+    /// let events = game.perform_action(&Action::PlayCard { player_idx: 0, details }).unwrap();
+    /// let kind = ChoiceKind::LookAtHand { first_idx: 1, second_idx: 2 };
+    /// assert_eq!(events.last(), Some(&Event::ChoiceRequired { player_idx: 0, kind }));
+    /// assert_eq!(game.current_player(), None);
+    /// assert_eq!(game.legal_choices(0), kind.options());
+    ///
+    /// let choice = Choice::LookAtHand { target_idx: 0 };
+    /// assert_eq!(game.perform_action(&Action::MakeChoice { player_idx: 0, choice }), Err(GameError::InvalidChoice(choice)));
+    ///
+    /// let choice = Choice::LookAtHand { target_idx: 2 };
+    /// let events = game.perform_action(&Action::MakeChoice { player_idx: 0, choice }).unwrap();
+    /// assert_eq!(events[0], Event::ShowCard { player_idx: 0, target_idx: 2, card: King });
+    /// assert_eq!(game.current_player(), Some(1));
+    /// ```
+    pub fn with_choices(mut self) -> Self {
+        self.asks_for_choices = true;
+        self
+    }
+
+    /// Get the choice which the card being played is waiting for, if any.
+    pub fn pending_choice(&self) -> Option<&PendingChoice> {
+        self.pending_choice.as_ref()
+    }
+
+    /// Get the custom deck used in place of the ruleset's cards, if any.
+    pub fn custom_deck(&self) -> Option<&CustomDeck> {
+        self.custom_deck.as_deref()
+    }
+
+    /// Get every card in the deck for this game, with repeats, in ascending order.
+    pub(crate) fn cards(&self) -> Vec<Card> {
+        match &self.custom_deck {
+            Some(deck) => deck.all_cards(),
+            None => self.ruleset.cards(),
+        }
+    }
+
+    /// Get the effect of the given card in this game.
+    pub fn effect(&self, card: Card) -> &dyn CardEffect {
+        self.effects.get(card)
+    }
+
+    /// Get the effect of every card in this game.
+    pub(crate) fn effects(&self) -> Arc<EffectRegistry> {
+        Arc::clone(&self.effects)
+    }
+
+    /// Get the number of players in the current game.
+    pub fn num_players(&self) -> usize {
+        self.players.len()
+    }
+
+    /// Get the players in the current game.
+    pub(crate) fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// Get the cards remaining in the deck, with the top of the deck last.
+    pub(crate) fn deck_cards(&self) -> &[Card] {
+        self.deck.cards()
+    }
+
+    /// Replace the cards remaining in the deck, with the top of the deck last.
+    pub(crate) fn set_deck_cards(&mut self, cards: Vec<Card>) {
+        self.deck = Deck::from_cards(cards);
+    }
+
+    /// Get the cards publicly removed from the game.
+    pub(crate) fn removed_cards(&self) -> &[Card] {
+        &self.removed_cards
+    }
+
+    /// Get the burned card, if it has not yet been drawn.
+    pub(crate) fn burned_card(&self) -> Option<Card> {
+        self.burned_card
+    }
+
+    /// Get the player whose turn it is, or was when the game ended.
+    pub(crate) fn turn_counter(&self) -> usize {
+        self.turn_counter
+    }
+
+    /// Check whether a game has been started.
+    pub(crate) fn has_started(&self) -> bool {
+        self.state != GameState::NotStarted
+    }
+
+    /// Check whether the game has finished.
+    pub fn is_over(&self) -> bool {
+        self.state == GameState::Complete
+    }
+
+    /// Get the player whose turn it is, or `None` if no game is in progress. This is also `None`
+    /// while the game is waiting for a player to make a choice, since no card can be played until
+    /// they have.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::Action;
+    /// # use love_letter::game::Game;
+    /// let mut game = Game::new();
+    /// assert_eq!(game.current_player(), None);
+    /// game.perform_action(&Action::StartGame { players: 3 }).unwrap();
+    /// assert_eq!(game.current_player(), Some(0));
+    /// ```
+    pub fn current_player(&self) -> Option<usize> {
+        match self.state {
+            GameState::InProgress if self.pending_choice.is_none() => Some(self.turn_counter),
+            _ => None,
+        }
+    }
+
+    /// Get everything that the given player is able to see about the current state of the game.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the player does not exist in the current game.
+    pub fn observation(&self, player_idx: usize) -> Observation {
+        Observation {
+            player_idx,
+            ruleset: self.ruleset,
+            num_players: self.players.len(),
+            current_player: self.current_player(),
+            hand: self.players[player_idx].hand().to_vec(),
+            history: self.players.iter().map(|p| p.history().to_vec()).collect(),
+            protected: self.players.iter().map(|p| p.protected()).collect(),
+            active: self.players.iter().map(|p| p.active()).collect(),
+            deck_size: self.deck.len(),
+            known_cards: self.knowledge[player_idx].clone(),
+            excluded_cards: self.exclusions.clone(),
+            removed_cards: self.removed_cards.clone(),
+            forced_target: self.forced_target,
+            marks: self.marks.clone(),
+            pending_choice: self.pending_choice,
+        }
+    }
+
+    /// Get the information set of the given player: everything they can see about the game, in a
+    /// canonical form suitable for use as a key in a transposition table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the player does not exist in the current game.
+    pub fn information_set(&self, player_idx: usize) -> InformationSet {
+        InformationSet::from(&self.observation(player_idx))
+    }
+
+    /// Sample a game which is indistinguishable from this one from the point of view of the given
+    /// player, with every card that they cannot see - the other players' hands, the burned card
+    /// and the order of the deck - redistributed uniformly at random among the arrangements
+    /// consistent with what they have observed.
+    ///
+    /// The arrangements considered consistent are those in which every card the player knows
+    /// about (having seen it with the Priest, Baron or King) is where they know it to be, and in
+    /// which no player holds a card that everybody has deduced they cannot be holding. Other
+    /// players' knowledge of each other's hands is carried over to the cards they now hold.
+    /// Relationships between other players' hands, such as two players having tied a Baron
+    /// comparison, are not taken into account.
+    ///
+    /// Consistent arrangements are found by dealing at random and rejecting deals which break a
+    /// deduction. If none is found within `MAX_SAMPLING_ATTEMPTS` deals, which can happen when the
+    /// deductions rule out almost every arrangement, `None` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the player does not exist in the current game.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rand::SeedableRng;
+    /// # use rand::rngs::StdRng;
+    /// # use love_letter::action::Action;
+    /// # use love_letter::game::Game;
+    /// let mut game = Game::new();
+    /// game.perform_action(&Action::StartGame { players: 4 }).unwrap();
+    ///
+    /// let mut rng = StdRng::seed_from_u64(0);
+    /// for _ in 0..100 {
+    ///     let sample = game.sample_determinization(2, &mut rng).unwrap();
+    ///     assert_eq!(sample.information_set(2), game.information_set(2));
+    /// }
+    /// ```
+    pub fn sample_determinization<R: Rng + ?Sized>(&self, player_idx: usize, rng: &mut R) -> Option<Game> {
+        let mut game = self.clone();
+        game.rng = StdRng::seed_from_u64(rng.gen());
+        if !self.has_started() {
+            return Some(game);
+        }
+
+        // Work out which cards the player cannot see.
+        let mut unseen = self.cards();
+        let seen = self.players.iter().flat_map(|player| player.history().iter().map(|discard| &discard.card))
+            .chain(self.players[player_idx].hand().iter())
+            .chain(self.removed_cards.iter());
+        for card in seen {
+            let position = unseen.iter().position(|c| c == card).unwrap();
+            unseen.swap_remove(position);
+        }
+
+        // Cards that the player knows another player to be holding stay where they are. Any other
+        // card held by another player may be replaced, as long as it doesn't break a deduction.
+        let mut hands = vec![Vec::new(); self.players.len()];
+        let mut free_slots = Vec::new();
+        for (idx, player) in self.players.iter().enumerate().filter(|&(idx, _)| idx != player_idx) {
+            for slot in 0..player.hand().len() {
+                match self.knowledge[player_idx][idx] {
+                    Some(card) if slot == 0 => {
+                        let position = unseen.iter().position(|&c| c == card).unwrap();
+                        unseen.swap_remove(position);
+                        hands[idx].push(card);
+                    }
+                    _ => free_slots.push((idx, slot)),
+                }
+            }
+        }
+
+        // Deal the remaining unseen cards at random until the deductions about which cards each
+        // player is not holding are all satisfied. Only a player's first card is subject to these,
+        // since any second card was drawn after the deductions were made.
+        let consistent = (0..MAX_SAMPLING_ATTEMPTS).any(|_| {
+            unseen.shuffle(rng);
+            free_slots.iter().zip(unseen.iter())
+                .all(|(&(idx, slot), card)| slot != 0 || !self.exclusions[idx].contains(card))
+        });
+        if !consistent {
+            return None;
+        }
+
+        let mut cards = unseen.into_iter();
+        for &(idx, _) in &free_slots {
+            hands[idx].push(cards.next().unwrap());
+        }
+        if game.burned_card.is_some() {
+            game.burned_card = cards.next();
+        }
+        game.deck = Deck::from_cards(cards.collect());
+
+        for (idx, hand) in hands.into_iter().enumerate().filter(|&(idx, _)| idx != player_idx) {
+            game.players[idx].set_hand(hand);
+        }
+
+        // Carry other players' knowledge over to the cards now held.
+        for observer in (0..self.players.len()).filter(|&observer| observer != player_idx) {
+            for target in (0..self.players.len()).filter(|&target| target != player_idx) {
+                if self.knowledge[observer][target].is_some() {
+                    game.knowledge[observer][target] = game.players[target].hand().first().copied();
+                }
+            }
+        }
+
+        Some(game)
+    }
+
+    /// Compute a stable Zobrist-style hash of the full state of the game, including the order of
+    /// the deck and every player's hand.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::Action;
+    /// # use love_letter::game::Game;
+    /// let mut first = Game::with_seed(1);
+    /// let mut second = Game::with_seed(1);
+    /// first.perform_action(&Action::StartGame { players: 2 }).unwrap();
+    /// second.perform_action(&Action::StartGame { players: 2 }).unwrap();
+    /// assert_eq!(first, second);
+    /// assert_eq!(first.zobrist_hash(), second.zobrist_hash());
+    ///
+    /// let details = first.legal_plays(0)[0].clone();
+    /// first.perform_action(&Action::PlayCard { player_idx: 0, details }).unwrap();
+    /// assert_ne!(first.zobrist_hash(), second.zobrist_hash());
+    /// ```
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = hashing::key(Feature::State, self.state as usize, 0, 0)
+            ^ hashing::key(Feature::Players, self.players.len(), 0, 0)
+            ^ hashing::key(Feature::DeckSize, self.deck.len(), 0, 0);
+
+        if let Some(player_idx) = self.current_player() {
+            hash ^= hashing::key(Feature::Turn, player_idx, 0, 0);
+        }
+        for (position, &card) in self.deck.cards().iter().enumerate() {
+            hash ^= hashing::key(Feature::DeckCard, position, card.number(), 0);
+        }
+        if let Some(card) = self.burned_card {
+            hash ^= hashing::key(Feature::BurnedCard, card.number(), 0, 0);
+        }
+
+        for (player_idx, player) in self.players.iter().enumerate() {
+            for (value, &count) in hashing::card_counts(player.hand().iter().copied()).iter().enumerate().filter(|(_, &count)| count > 0) {
+                hash ^= hashing::key(Feature::HandCount, player_idx, value, count as usize);
+            }
+            for (value, &count) in hashing::card_counts(player.discards()).iter().enumerate().filter(|(_, &count)| count > 0) {
+                hash ^= hashing::key(Feature::DiscardCount, player_idx, value, count as usize);
+            }
+            if player.protected() {
+                hash ^= hashing::key(Feature::Protected, player_idx, 0, 0);
+            }
+            if player.active() {
+                hash ^= hashing::key(Feature::Active, player_idx, 0, 0);
+            }
+            for (target_idx, known) in self.knowledge[player_idx].iter().enumerate() {
+                if let Some(card) = known {
+                    hash ^= hashing::key(Feature::KnownCard, player_idx, target_idx, card.number());
+                }
+            }
+            for &card in &self.exclusions[player_idx] {
+                hash ^= hashing::key(Feature::ExcludedCard, player_idx, card.number(), 0);
+            }
+        }
+        for (value, &count) in hashing::card_counts(self.removed_cards.iter().copied()).iter().enumerate().filter(|(_, &count)| count > 0) {
+            hash ^= hashing::key(Feature::RemovedCount, value, count as usize, 0);
+        }
+        hash ^= hashing::rules_hash(self.ruleset, self.forced_target, &self.marks, self.pending_choice.as_ref());
+
+        hash
+    }
+
+    /// Get every play that the given player could legally make at this point in the game. The
+    /// list is empty if it is not that player's turn.
+    ///
+    /// Cards such as the Guard are never listed with a guess of 1, since the rules do not allow it,
+    /// except when playing with a custom deck. Guesses are only listed once for each value.
+    /// Targets beyond those a card requires, such as the Baroness's optional second target, are
+    /// listed in ascending order of seat.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::{Action, PlayCardDetails};
+    /// # use love_letter::game::Game;
+    /// let mut game = Game::new();
+    /// game.perform_action(&Action::StartGame { players: 2 }).unwrap();
+    /// for details in game.legal_plays(0) {
+    ///     let mut copy = game.clone();
+    ///     assert!(copy.perform_action(&Action::PlayCard { player_idx: 0, details }).is_ok());
+    /// }
+    /// assert!(game.legal_plays(1).is_empty());
+    /// ```
+    pub fn legal_plays(&self, player_idx: usize) -> Vec<PlayCardDetails> {
+        if self.current_player() != Some(player_idx) {
+            return Vec::new();
+        }
+
+        let mut hand = self.players[player_idx].hand().to_vec();
+        hand.sort();
+        hand.dedup();
+
+        let mut plays = Vec::new();
+        for card in hand {
+            if self.is_player_allowed_to_play_card(player_idx, card).is_err() {
+                continue;
+            }
+
+            // List every combination of targets that might be allowed, and let validation decide.
+            let effect = self.effect(card);
+            let targeting = effect.targeting();
+            let players = self.players.len();
+            let mut target_lists = vec![Vec::new()];
+            if targeting.required <= 1 && targeting.max >= 1 {
+                target_lists.extend((0..players).map(|target_idx| vec![target_idx]));
+            }
+            if targeting.required == 2 {
+                target_lists.extend((0..players).flat_map(|first| (0..players).filter(move |&second| second != first).map(move |second| vec![first, second])));
+            } else if targeting.max >= 2 {
+                target_lists.extend((0..players).flat_map(|first| (first + 1..players).map(move |second| vec![first, second])));
+            }
+
+            for targets in target_lists {
+                let candidates = if effect.takes_guess() && !targets.is_empty() {
+                    self.guesses().into_iter()
+                        .filter_map(|guess| PlayCardDetails::from_targets(card, &targets, Some(guess)))
+                        .collect()
+                } else {
+                    PlayCardDetails::from_targets(card, &targets, None).into_iter().collect::<Vec<_>>()
+                };
+                plays.extend(candidates.into_iter().filter(|details| self.validate_play(player_idx, details).is_ok()));
+            }
+        }
+
+        plays
+    }
+
+    /// Get every choice that the given player could make at this point in the game. The list is
+    /// empty unless the game is waiting for that player to make a choice.
+    pub fn legal_choices(&self, player_idx: usize) -> Vec<Choice> {
+        match &self.pending_choice {
+            Some(pending) if pending.player_idx == player_idx => pending.kind.options(),
+            _ => Vec::new(),
         }
     }
 
+    /// Get the guesses worth making with a card such as the Guard: one card of each value in the
+    /// deck other than 1, preferring the classic card where several share a value. With a custom
+    /// deck, every value is worth guessing.
+    fn guesses(&self) -> Vec<Card> {
+        let mut guesses = self.cards_in_deck()
+            .filter(|&card| self.custom_deck.is_some() || self.effect(card).value() != 1)
+            .collect::<Vec<_>>();
+        guesses.sort_by_key(|&card| self.effect(card).value());
+        guesses.dedup_by_key(|&mut card| self.effect(card).value());
+        guesses
+    }
+
     /// Carry out the given action on the game, returning all of the events which occur as a result.
     ///
     /// The first action that _must_ be carried out a new `Game` is a `StartGame` action, in order
     /// to shuffle the deck and set up the players. Any other action is invalid before `StartGame`
     /// has been carried out.
     ///
+    /// This never panics, whatever the action. An action which is not allowed is rejected with the
+    /// same `GameError` as `validate` would give, and leaves the game unchanged. The exception is
+    /// `GameError::InconsistentState`, which is only returned once the action is already under way
+    /// and may leave it partly carried out. Similarly, in debug builds the invariants described by
+    /// `check_invariants` are checked after every action, and a violation (which would indicate a
+    /// bug in the engine) causes a panic.
+    ///
     /// # Examples
     ///
     /// ```
@@ -76,28 +732,85 @@ impl Game {
     /// let events = game.perform_action(&Action::StartGame { players: 2 });
     /// ```
     pub fn perform_action(&mut self, action: &Action) -> Result<Vec<Event>, GameError> {
-        match action {
+        let result = match action {
             Action::StartGame { players } => self.start_game(*players),
             Action::PlayCard { player_idx, details } => self.play_card(*player_idx, details),
+            Action::MakeChoice { player_idx, choice } => self.make_choice(*player_idx, *choice),
+            Action::Forfeit { player_idx } => self.forfeit(*player_idx),
+            Action::Timeout { player_idx } => self.time_out(*player_idx),
+        };
+
+        #[cfg(debug_assertions)]
+        {
+            if let Err(violation) = self.check_invariants() {
+                panic!("Game invariant violated after {:?}: {}", action, violation);
+            }
+        }
+
+        result
+    }
+
+    /// Check whether the given action would succeed, without carrying it out. The error returned
+    /// is the same one that `perform_action` would return, apart from
+    /// `GameError::InconsistentState`, which only comes to light while carrying the action out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::{Action, PlayCardDetails};
+    /// # use love_letter::environment;
+    /// # use love_letter::game::{Game, GameError};
+    /// let mut game = Game::new();
+    /// assert_eq!(game.validate(&Action::StartGame { players: 5 }), Err(GameError::InvalidNumberOfPlayers(5)));
+    /// game.perform_action(&Action::StartGame { players: 3 }).unwrap();
+    ///
+    /// let details = PlayCardDetails::PlayHandmaid {};
+    /// assert_eq!(game.validate(&Action::PlayCard { player_idx: 1, details }), Err(GameError::PlayedOutOfTurn(1)));
+    ///
+    /// // Validation agrees with actually performing the action.
+    /// for details in environment::action_space() {
+    ///     let action = Action::PlayCard { player_idx: 0, details };
+    ///     assert_eq!(game.validate(&action), game.clone().perform_action(&action).map(|_| ()));
+    /// }
+    /// ```
+    pub fn validate(&self, action: &Action) -> Result<(), GameError> {
+        match action {
+            Action::StartGame { players } => {
+                self.is_number_of_players_valid(*players)?;
+                self.is_deck_valid(*players)
+            }
+            Action::PlayCard { player_idx, details } => self.validate_play(*player_idx, details),
+            Action::MakeChoice { player_idx, choice } => self.validate_choice(*player_idx, *choice),
+            Action::Forfeit { player_idx } => self.validate_forfeit(*player_idx),
+            Action::Timeout { player_idx } => self.validate_timeout(*player_idx),
         }
     }
 
     /// Start a new game with the given number of players.
     fn start_game(&mut self, players: usize) -> Result<Vec<Event>, GameError> {
 
-        // Check that the number of players is legal for a game of Love Letter.
-        if players < 2 || players > 4 {
-            return Err(GameError::InvalidNumberOfPlayers(players));
-        }
+        // Check that the number of players is legal for a game of Love Letter, and that there are
+        // enough cards for them.
+        self.is_number_of_players_valid(players)?;
+        self.is_deck_valid(players)?;
 
         // The events that will result from this action being carried out.
         let mut events = vec![Event::NewGame { players }];
 
         // Reset the deck and shuffle it.
-        self.deck = Deck::new();
-        self.deck.shuffle();
+        self.deck = match &self.custom_deck {
+            Some(deck) => Deck::from_cards(deck.all_cards()),
+            None => Deck::for_ruleset(self.ruleset),
+        };
+        self.deck.shuffle(&mut self.rng);
+        self.forced_target = None;
+        self.marks.clear();
+        self.pending_choice = None;
 
         // Register players with the game.
+        self.knowledge = vec![vec![None; players]; players];
+        self.exclusions = vec![Vec::new(); players];
+        self.removed_cards.clear();
         self.players.clear();
         for player_idx in 0..players {
             self.players.push(Player::new());
@@ -111,20 +824,20 @@ impl Game {
         // In a two player game, additionally discard three cards from the deck.
         if players == 2 {
             for _ in 0..3 {
-                events.push(Event::RemoveCardFromGame {
-                    card: self.deck.pop().unwrap(),
-                })
+                let card = self.deck.pop().ok_or(GameError::InconsistentState)?;
+                self.removed_cards.push(card);
+                events.push(Event::RemoveCardFromGame { card })
             }
         }
 
         // Deal a card to each player
         for player_idx in 0..players {
-            events.push(self.draw_and_give_card_to_player(player_idx));
+            events.push(self.draw_and_give_card_to_player(player_idx)?);
         }
 
         // Deal an additional card to the first player and inform them that they are ready to play
-        events.push(self.draw_and_give_card_to_player(0));
-        events.push(self.start_player_turn(0));
+        events.push(self.draw_and_give_card_to_player(0)?);
+        events.extend(self.start_player_turn(0));
 
         // Set the game as in-progress and return the events that have occurred back to the caller.
         self.state = GameState::InProgress;
@@ -134,151 +847,233 @@ impl Game {
     /// Determine the events resulting from a player playing a card.
     fn play_card(&mut self, player_idx: usize, details: &PlayCardDetails) -> Result<Vec<Event>, GameError> {
 
-        use PlayCardDetails::*;
-
         // Events resulting from this action.
         let mut events = Vec::new();
 
-        // Check that a game is in progress.
-        self.is_game_in_progress()?;
-
-        // Perform common checks - is it this player's turn, have they chosen a valid target, and 
-        // are they holding the card they want to play? If so, remove it from their hand.
-        self.does_player_exist(player_idx)?;
-        self.is_it_players_turn(player_idx)?;
-        self.is_target_valid(player_idx, details.target(), details.card())?;
-        self.is_player_allowed_to_play_card(player_idx, details.card())?;
+        // Check that the play is allowed, and if so, remove the card from the player's hand. Any
+        // target forced by the previous play only applies to this one.
+        self.validate_play(player_idx, details)?;
         events.push(self.play_card_from_player_hand(player_idx, details.card())?);
-
-        // Describe the events specifically resulting from the play of the card.
-        events.append(&mut match details {
-            PlayGuard { target_idx: Some(target_idx), guess } => self.play_guard(*target_idx, *guess),
-            PlayPriest { target_idx: Some(target_idx) } => self.play_priest(player_idx, *target_idx),
-            PlayBaron { target_idx: Some(target_idx) } => self.play_baron(player_idx, *target_idx),
-            PlayHandmaid {} => self.play_handmaid(player_idx),
-            PlayPrince { target_idx } => self.play_prince(*target_idx),
-            PlayKing { target_idx: Some(target_idx) } => self.play_king(player_idx, *target_idx),
-            PlayCountess {} => self.play_countess(),
-            PlayPrincess {} => self.play_princess(player_idx),
-            _ => Ok(Vec::new()),
-        }?);
-
-        // Check if there is only one player left standing
-        let active_players = self.active_players();
-        if active_players.len() == 1 {
-            events.push(self.end_game_with_winners(active_players));
+        self.forced_target = None;
+
+        // A card played without the targets it requires has no effect. Otherwise, the card held by
+        // the first target may react to being targeted, and if it doesn't, the card takes effect.
+        let effects = self.effects();
+        let effect = effects.get(details.card());
+        let targets = details.targets();
+        if targets.len() >= effect.targeting().required {
+            let reaction = match targets.first() {
+                Some(&target_idx) if target_idx != player_idx => {
+                    let card = self.held_card(target_idx)?;
+                    effects.get(card).on_targeted(&mut EffectContext::new(self), target_idx, player_idx, details)?
+                }
+                _ => None,
+            };
+            events.extend(match reaction {
+                Some(reaction) => reaction,
+                None => effect.apply(&mut EffectContext::new(self), player_idx, details)?,
+            });
+        } else {
+            events.push(Event::NoValidTarget { player_idx, card: details.card() });
         }
 
-        // Otherwise, check if the deck is empty
-        else if self.deck.is_empty() {
-            let winners = self.calculate_winners();
-            events.push(self.end_game_with_winners(winners));
-        }
+        self.finish_turn(events)
+    }
 
-        // Otherwise, it's the next player's turn
-        else {
-            let next_player = self.next_player();
-            events.push(self.draw_and_give_card_to_player(next_player));
-            events.push(self.start_player_turn(next_player));
-        }
+    /// Resolve the choice that the card being played is waiting for.
+    fn make_choice(&mut self, player_idx: usize, choice: Choice) -> Result<Vec<Event>, GameError> {
+        self.validate_choice(player_idx, choice)?;
+        let events = self.resolve_choice(choice)?;
+        self.finish_turn(events)
+    }
 
-        Ok(events)
+    /// Eliminate a player who forfeits.
+    fn forfeit(&mut self, player_idx: usize) -> Result<Vec<Event>, GameError> {
+        self.validate_forfeit(player_idx)?;
+        self.remove_player(player_idx, EliminationReason::Forfeit)
     }
 
-    /// Determine the events resulting from a player playing a Guard.
-    fn play_guard(&mut self, target_idx: usize, guess: Card) -> Result<Vec<Event>, GameError> {
+    /// Eliminate a player who has run out of time to make their move.
+    fn time_out(&mut self, player_idx: usize) -> Result<Vec<Event>, GameError> {
+        self.validate_timeout(player_idx)?;
+        self.remove_player(player_idx, EliminationReason::Timeout)
+    }
+
+    /// Eliminate a player who leaves the game, revealing their hand. If it was their turn, or the
+    /// card being played was waiting for them to make a choice, the rest of the turn is abandoned
+    /// and the game moves on to the next player. Otherwise, the game ends if only one player
+    /// remains.
+    fn remove_player(&mut self, player_idx: usize, reason: EliminationReason) -> Result<Vec<Event>, GameError> {
+
+        // Unlike other eliminations, this doesn't give the effects of the player's discards a
+        // chance to react, so that nobody can gain a token with the Constable by walking away.
+        let mut events = vec![self.eliminate_player(player_idx, reason)];
+        for card in self.players[player_idx].take_hand(DiscardKind::Revealed) {
+            events.push(Event::RevealCard { player_idx, card });
+        }
 
-        // The target has a guess made about their hand.
-        let mut events = vec![Event::Guess { target_idx, guess }];
+        let is_choosing = self.pending_choice.is_some_and(|pending| pending.player_idx == player_idx);
+        if self.turn_counter == player_idx || is_choosing {
+            self.pending_choice = None;
+            return self.finish_turn(events);
+        }
 
-        // Record that the target player is eliminated, and reveal the card in their hand.
-        if self.players[target_idx].is_holding_card(guess) {
-            events.push(self.eliminate_player(target_idx));
-            events.push(self.reveal_eliminated_player_card(target_idx));
+        // If the player's hand was one of two to choose between looking at, look at the other.
+        if let Some(PendingChoice { kind: ChoiceKind::LookAtHand { first_idx, second_idx }, .. }) = self.pending_choice {
+            if first_idx == player_idx || second_idx == player_idx {
+                let target_idx = if first_idx == player_idx { second_idx } else { first_idx };
+                events.extend(self.resolve_choice(Choice::LookAtHand { target_idx })?);
+                return self.finish_turn(events);
+            }
         }
 
+        let active_players = self.active_players();
+        if active_players.len() <= 1 {
+            self.pending_choice = None;
+            events.extend(self.end_game_with_winners(active_players, WinReason::LastPlayerStanding)?);
+        }
+        self.update_knowledge(&events);
         Ok(events)
     }
 
-    /// Determine the events resulting from a player playing a Priest.
-    fn play_priest(&mut self, player_idx: usize, target_idx: usize) -> Result<Vec<Event>, GameError> {
+    /// Finish the turn once the card played has taken effect, given the events so far. Any choice
+    /// the card calls for is made on the player's behalf, unless players are asked to make their
+    /// own choices, in which case the turn stops until it is made.
+    fn finish_turn(&mut self, mut events: Vec<Event>) -> Result<Vec<Event>, GameError> {
+        while let Some(pending) = self.pending_choice {
+            if self.asks_for_choices {
+                events.push(Event::ChoiceRequired { player_idx: pending.player_idx, kind: pending.kind });
+                self.update_knowledge(&events);
+                return Ok(events);
+            }
+            events.extend(self.resolve_choice(pending.kind.default_choice())?);
+        }
+        events.push(Event::TurnEnded { player_idx: self.turn_counter });
 
-        // The target must show their hand.
-        let card = self.players[target_idx].card().unwrap();
-        Ok(vec![Event::ShowCard { player_idx, target_idx, card }])
-    }
+        // End the round if it is over, or else move on to the next player who can take a turn
+        loop {
 
-    /// Determine the events resulting from a player playing a Baron.
-    fn play_baron(&mut self, player_idx: usize, target_idx: usize) -> Result<Vec<Event>, GameError> {
+            // Check if a card such as Cthulhu has won the round outright
+            if let Some(winner_idx) = self.round_winner.take() {
+                events.extend(self.end_game_with_winners(vec![winner_idx], WinReason::CardEffect)?);
+                break;
+            }
 
-        // The player and the target compare their hands.
-        let player_card = self.players[player_idx].card().unwrap();
-        let target_card = self.players[target_idx].card().unwrap();
-        let mut events = vec![Event::CompareHands { player_idx, player_card, target_idx, target_card }];
+            // Otherwise, check if there is only one player left standing, or none at all if a
+            // custom card has eliminated everybody who remained
+            let active_players = self.active_players();
+            if active_players.len() <= 1 {
+                events.extend(self.end_game_with_winners(active_players, WinReason::LastPlayerStanding)?);
+                break;
+            }
 
-        // The loser is eliminated from the game
-        if let Some(losing_player_idx) = if player_card < target_card { Some(player_idx) } else if target_card < player_card { Some(target_idx) } else { None } {
-            events.push(self.eliminate_player(losing_player_idx));
-            events.push(self.reveal_eliminated_player_card(losing_player_idx));
+            // Otherwise, check if the deck is empty
+            if self.deck.is_empty() {
+                let (winners, reason) = self.calculate_winners()?;
+                events.extend(self.end_game_with_winners(winners, reason)?);
+                break;
+            }
+
+            // Otherwise, it's the next player's turn, as long as they survive their insanity check
+            // if they are insane. If they don't, check again whether the round is over.
+            let next_player = self.next_player()?;
+            events.extend(EffectContext::new(self).insanity_check(next_player)?);
+            if self.players[next_player].active() {
+                events.push(self.draw_and_give_card_to_player(next_player)?);
+                events.extend(self.start_player_turn(next_player));
+                break;
+            }
         }
 
+        self.update_knowledge(&events);
         Ok(events)
     }
 
-    /// Determine the events resulting from a player playing a Handmaid.
-    fn play_handmaid(&mut self, player_idx: usize) -> Result<Vec<Event>, GameError> {
-        self.players[player_idx].make_protected();
-        Ok(Vec::new())
+    /// Finish the effect of the card waiting for a choice, with the given choice.
+    fn resolve_choice(&mut self, choice: Choice) -> Result<Vec<Event>, GameError> {
+        let pending = self.pending_choice.take().ok_or(GameError::InconsistentState)?;
+        let effects = self.effects();
+        effects.get(pending.card).resolve_choice(&mut EffectContext::new(self), &pending, choice)
     }
 
-    /// Determine the events resulting from a player playing a Prince.
-    fn play_prince(&mut self, target_idx: usize) -> Result<Vec<Event>, GameError> {
-
-        // The target is forced to discard their hand.
-        let card = self.players[target_idx].card().unwrap();
-        let mut events = vec![self.discard_hand(target_idx)];
-
-        // If the discarded card is the Princess, then the player is eliminated.
-        if card == Card::Princess {
-            events.push(self.eliminate_player(target_idx));
+    /// Check that the game is currently in progress.
+    fn is_game_in_progress(&self) -> Result<(), GameError> {
+        match self.state {
+            GameState::NotStarted | GameState::Complete => Err(GameError::GameNotInProgress),
+            GameState::InProgress => Ok(()),
         }
+    }
 
-        // Otherwise, draw a new card and give it to the player.
-        else {
-            events.push(self.draw_and_give_card_to_player(target_idx));
+    /// Check that the number of players is legal for a game of Love Letter under the ruleset.
+    fn is_number_of_players_valid(&self, players: usize) -> Result<(), GameError> {
+        if self.ruleset.players().contains(&players) {
+            Ok(())
+        } else {
+            Err(GameError::InvalidNumberOfPlayers(players))
         }
+    }
 
-        Ok(events)
+    /// Check that the custom deck, if there is one, is valid for a game with the given number of
+    /// players.
+    fn is_deck_valid(&self, players: usize) -> Result<(), GameError> {
+        match &self.custom_deck {
+            Some(deck) => deck.validate(players).map_err(GameError::InvalidDeck),
+            None => Ok(()),
+        }
     }
 
-    /// Determine the events resulting from a player playing a King.
-    fn play_king(&mut self, player_idx: usize, target_idx: usize) -> Result<Vec<Event>, GameError> {
-        
-        // The target and the player swap their hands
-        let player_card = self.players[player_idx].take_card().unwrap();
-        let target_card = self.players[target_idx].take_card().unwrap();
-        self.players[player_idx].give_card(target_card);
-        self.players[target_idx].give_card(player_card);
-        Ok(vec![Event::SwapHands { player_idx, player_card, target_idx, target_card }])
+    /// Perform every check on a play - is a game in progress, is it this player's turn, have they
+    /// chosen a valid target, and are they holding the card they want to play?
+    fn validate_play(&self, player_idx: usize, details: &PlayCardDetails) -> Result<(), GameError> {
+        self.is_game_in_progress()?;
+        self.does_player_exist(player_idx)?;
+        self.is_no_choice_pending()?;
+        self.is_it_players_turn(player_idx)?;
+        self.is_target_valid(player_idx, details)?;
+        self.is_guess_valid(details)?;
+        self.is_player_allowed_to_play_card(player_idx, details.card())?;
+        self.is_player_holding_card(player_idx, details.card())
     }
 
-    /// Determine the events resulting from a player playing a Countess.
-    fn play_countess(&mut self) -> Result<Vec<Event>, GameError> {
-        Ok(Vec::new())
+    /// Perform every check on a choice - is a game in progress, is a choice waiting to be made by
+    /// this player, and is this one of the options?
+    fn validate_choice(&self, player_idx: usize, choice: Choice) -> Result<(), GameError> {
+        self.is_game_in_progress()?;
+        self.does_player_exist(player_idx)?;
+        let pending = self.pending_choice.as_ref().ok_or(GameError::NoChoicePending)?;
+        if pending.player_idx != player_idx {
+            Err(GameError::ChoiceOutOfTurn(player_idx))
+        } else if !pending.kind.options().contains(&choice) {
+            Err(GameError::InvalidChoice(choice))
+        } else {
+            Ok(())
+        }
     }
 
-    /// Determine the events resulting from a player playing a Princess.
-    fn play_princess(&mut self, player_idx: usize) -> Result<Vec<Event>, GameError> {
+    /// Check that a player may forfeit - is a game in progress, and are they still in it?
+    fn validate_forfeit(&self, player_idx: usize) -> Result<(), GameError> {
+        self.is_game_in_progress()?;
+        self.does_player_exist(player_idx)?;
+        if self.players[player_idx].active() { Ok(()) } else { Err(GameError::PlayerEliminated(player_idx)) }
+    }
 
-        // The player is immediately eliminated
-        Ok(vec![self.eliminate_player(player_idx)])
+    /// Check that a player may be timed out - is a game in progress, and is it their move?
+    fn validate_timeout(&self, player_idx: usize) -> Result<(), GameError> {
+        self.is_game_in_progress()?;
+        self.does_player_exist(player_idx)?;
+        let is_choosing = self.pending_choice.is_some_and(|pending| pending.player_idx == player_idx);
+        if self.current_player() == Some(player_idx) || is_choosing {
+            Ok(())
+        } else {
+            Err(GameError::PlayedOutOfTurn(player_idx))
+        }
     }
 
-    /// Check that the game is currently in progress.
-    fn is_game_in_progress(&self) -> Result<(), GameError> {
-        match self.state {
-            GameState::NotStarted | GameState::Complete => Err(GameError::GameNotInProgress),
-            GameState::InProgress => Ok(()),
+    /// Check that no choice is waiting to be made before the next card can be played.
+    fn is_no_choice_pending(&self) -> Result<(), GameError> {
+        match &self.pending_choice {
+            Some(pending) => Err(GameError::ChoicePending(pending.player_idx)),
+            None => Ok(()),
         }
     }
 
@@ -300,60 +1095,89 @@ impl Game {
         }
     }
 
-    /// Check the the player is allowed to play a particular card.
+    /// Check the the player is allowed to play a particular card, given the rest of their hand.
     fn is_player_allowed_to_play_card(&self, player_idx: usize, card: Card) -> Result<(), GameError> {
-
-        // The Prince and King cannot be played if the player also holds a Countess
-        if card == Card::Prince || card == Card::King {
-            if self.players[player_idx].is_holding_card(Card::Countess) {
-                return Err(GameError::CannotPlayWhileHoldingCountess(card));
-            }
+        let mut others = self.players[player_idx].hand().to_vec();
+        if let Some(position) = others.iter().position(|&other| other == card) {
+            others.remove(position);
         }
-
-        Ok(())
+        others.iter().try_for_each(|&other| self.effect(other).restrict(card))
     }
 
-    /// Check that the target player exists and is allowed to be targeted by the given card.
-    fn is_target_valid(&self, player_idx: usize, target_idx: Option<usize>, card: Card) -> Result<(), GameError> {
-
-        // If there are unprotected targets and this card is one that requires a target, then a
-        // target must be given.
-        if card.has_target() {
-            let unprotected_targets = self.unprotected_targets(player_idx, card == Card::Prince);
-            if !unprotected_targets.is_empty() && target_idx.is_none() {
-                return Err(GameError::MustProvideTarget(card));
-            }
+    /// Check that the player is holding the card they want to play.
+    fn is_player_holding_card(&self, player_idx: usize, card: Card) -> Result<(), GameError> {
+        if self.players[player_idx].is_holding_card(card) {
+            Ok(())
+        } else {
+            Err(GameError::PlayerDoesNotHaveCard(player_idx, card))
         }
+    }
 
-        // Targeting oneself is only possible when the card being played is the Prince.
-        if card != Card::Prince && target_idx == Some(player_idx) {
-            return Err(GameError::CannotTargetSelf(card));
+    /// Check that the target players exist and are allowed to be targeted by the given play.
+    fn is_target_valid(&self, player_idx: usize, details: &PlayCardDetails) -> Result<(), GameError> {
+        let card = details.card();
+        let targeting = self.effect(card).targeting();
+        let targets = details.targets();
+
+        // If there are enough unprotected targets for this card, then it must be given as many
+        // targets as it requires. If there are too few, it must be given none at all.
+        let required = targeting.required;
+        let unprotected_targets = self.unprotected_targets(player_idx, targeting.include_self);
+        let enough_targets = unprotected_targets.len() >= required;
+        if targets.len() < required && (enough_targets || !targets.is_empty()) {
+            return Err(GameError::MustProvideTarget(card));
         }
 
-        // Targeting a non-existent player is not allowed.
-        if let Some(idx) = target_idx {
+        for (position, &idx) in targets.iter().enumerate() {
+
+            // Targeting oneself is only possible with cards such as the Prince.
+            if !targeting.include_self && idx == player_idx {
+                return Err(GameError::CannotTargetSelf(card));
+            }
+
+            // Targeting a non-existent player is not allowed.
             if idx >= self.players.len() {
                 return Err(GameError::PlayerDoesNotExist(idx));
             }
-        }
 
-        // Targeting a protected player is not allowed.
-        if let Some(idx) = target_idx {
+            // Targeting a protected player is not allowed.
             if self.players[idx].protected() {
                 return Err(GameError::CannotTargetProtectedPlayer);
             }
-        }
 
-        // Targeting an eliminated player is not allowed.
-        if let Some(idx) = target_idx {
+            // Targeting an eliminated player is not allowed.
             if !self.players[idx].active() {
                 return Err(GameError::CannotTargetEliminatedPlayer);
             }
+
+            // Targeting the same player twice is not allowed.
+            if targets[..position].contains(&idx) {
+                return Err(GameError::CannotTargetPlayerTwice(idx));
+            }
+        }
+
+        // A player chosen with a card such as the Sycophant must be targeted, if this card can
+        // target them.
+        if let Some(forced_idx) = self.forced_target {
+            if targeting.has_target() && enough_targets && unprotected_targets.contains(&forced_idx) && !targets.contains(&forced_idx) {
+                return Err(GameError::MustTargetPlayer(forced_idx));
+            }
         }
 
         Ok(())
     }
 
+    /// Check that a play with a target has a guess, if its card calls for one. Only custom cards
+    /// can be played without a guess when they need one.
+    fn is_guess_valid(&self, details: &PlayCardDetails) -> Result<(), GameError> {
+        let card = details.card();
+        if self.effect(card).takes_guess() && details.target().is_some() && details.guess().is_none() {
+            Err(GameError::MustProvideGuess(card))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Check that the player is holding the card they want to play, and remove it from their hand
     /// if they are.
     fn play_card_from_player_hand(&mut self, player_idx: usize, card: Card) -> Result<Event, GameError> {
@@ -364,41 +1188,201 @@ impl Game {
     }
 
     /// Draw a card, deal it to a player, and return an `Event` summarising as much.
-    fn draw_and_give_card_to_player(&mut self, player_idx: usize) -> Event {
+    pub(crate) fn draw_and_give_card_to_player(&mut self, player_idx: usize) -> Result<Event, GameError> {
 
         // Use the deck if possible, or the burned card if the deck is empty.
-        let card = self.deck.pop().or(self.burned_card).take().unwrap();
+        let card = self.deck.pop().or_else(|| self.burned_card.take()).ok_or(GameError::InconsistentState)?;
         self.players[player_idx].give_card(card);
-        Event::DealCard { player_idx, card }
+        Ok(Event::DealCard { player_idx, card })
+    }
+
+    /// Get the single card held by the given player.
+    pub(crate) fn held_card(&self, player_idx: usize) -> Result<Card, GameError> {
+        self.players[player_idx].card().ok_or(GameError::InconsistentState)
     }
 
-    /// Begin a player's turn.
-    fn start_player_turn(&mut self, player_idx: usize) -> Event {
+    /// Replace the single card held by the given player.
+    pub(crate) fn set_held_card(&mut self, player_idx: usize, card: Card) {
+        self.players[player_idx].exchange_card(card);
+    }
+
+    /// Protect the given player until the start of their next turn.
+    pub(crate) fn protect_player(&mut self, player_idx: usize) {
+        self.players[player_idx].make_protected();
+    }
+
+    /// Set the player who must be targeted by the next card played, if possible.
+    pub(crate) fn set_forced_target(&mut self, target_idx: Option<usize>) {
+        self.forced_target = target_idx;
+    }
+
+    /// Get the player who has won the round outright through the card being played, if any.
+    pub(crate) fn round_winner(&self) -> Option<usize> {
+        self.round_winner
+    }
+
+    /// Make the given player the winner of the round once the card being played has taken effect.
+    pub(crate) fn set_round_winner(&mut self, player_idx: usize) {
+        self.round_winner = Some(player_idx);
+    }
+
+    /// Move the top card of the deck to the given player's discards, returning it, or `None` if it
+    /// is the last card in the deck, which must be left for the next player to draw.
+    pub(crate) fn reveal_top_card(&mut self, player_idx: usize) -> Option<Card> {
+        if self.deck.len() <= 1 {
+            return None;
+        }
+        let card = self.deck.pop()?;
+        self.players[player_idx].add_discard(card, DiscardKind::InsanityCheck);
+        Some(card)
+    }
+
+    /// Wait for the given choice to be made before finishing the effect of the card being played.
+    pub(crate) fn set_pending_choice(&mut self, pending: PendingChoice) {
+        self.pending_choice = Some(pending);
+    }
+
+    /// Record a choice whose effect comes at the end of the round.
+    pub(crate) fn add_mark(&mut self, mark: Mark) {
+        self.marks.push(mark);
+    }
+
+    /// Begin a player's turn, ending any protection they had.
+    fn start_player_turn(&mut self, player_idx: usize) -> Vec<Event> {
         self.turn_counter = player_idx;
-        self.players[player_idx].make_unprotected();
-        Event::ReadyToPlay { player_idx }
+        let mut events = Vec::new();
+        if self.players[player_idx].protected() {
+            self.players[player_idx].make_unprotected();
+            events.push(Event::ProtectionExpired { player_idx });
+        }
+        events.push(Event::ReadyToPlay { player_idx });
+        events
     }
 
     /// Discard the given player's hand.
-    fn discard_hand(&mut self, target_idx: usize) -> Event {
-        Event::DiscardCard { target_idx, card: self.players[target_idx].take_card().unwrap() }
+    pub(crate) fn discard_hand(&mut self, target_idx: usize) -> Result<Event, GameError> {
+        let card = self.players[target_idx].take_card(DiscardKind::Forced).ok_or(GameError::InconsistentState)?;
+        Ok(Event::DiscardCard { target_idx, card })
     }
 
-    /// Eliminate the given player from the game and return an `Event` to represent this.
-    fn eliminate_player(&mut self, player_idx: usize) -> Event {
+    /// Eliminate the given player from the game.
+    pub(crate) fn eliminate_player(&mut self, player_idx: usize, reason: EliminationReason) -> Event {
         self.players[player_idx].eliminate();
-        Event::EliminatePlayer { player_idx }
+        Event::EliminatePlayer { player_idx, reason }
     }
 
-    /// End the game with the given player as the winner.
-    fn end_game_with_winners(&mut self, winner_indices: Vec<usize>) -> Event {
+    /// End the game with the given players as the winners, after resolving any choices made this
+    /// round whose effect comes at the end of it.
+    fn end_game_with_winners(&mut self, winner_indices: Vec<usize>, reason: WinReason) -> Result<Vec<Event>, GameError> {
         self.state = GameState::Complete;
-        Event::GameOver { winner_indices }
+        let mut events = self.active_players().into_iter().map(|player_idx| {
+            let (card, score, discard_total) = self.final_score(player_idx)?;
+            Ok(Event::FinalHand { player_idx, card, score, discard_total })
+        }).collect::<Result<Vec<_>, GameError>>()?;
+        events.push(Event::WinnersDecided { reason });
+        events.extend(self.marks.iter().flat_map(|mark| self.effect(mark.card).on_round_end(mark, &winner_indices)));
+        events.push(Event::GameOver { winner_indices });
+        Ok(events)
     }
 
     /// Reveal the final card from an eliminated player's hand.
-    fn reveal_eliminated_player_card(&mut self, player_idx: usize) -> Event {
-        Event::RevealCard { player_idx, card: self.players[player_idx].take_card().unwrap() }
+    pub(crate) fn reveal_eliminated_player_card(&mut self, player_idx: usize) -> Result<Event, GameError> {
+        let card = self.players[player_idx].take_card(DiscardKind::Revealed).ok_or(GameError::InconsistentState)?;
+        Ok(Event::RevealCard { player_idx, card })
+    }
+
+    /// Update what each player knows about the others' hands in light of the given events.
+    fn update_knowledge(&mut self, events: &[Event]) {
+        let effects = self.effects();
+        let mut played = None;
+        for (position, event) in events.iter().enumerate() {
+            match *event {
+
+                // A player who plays the card they were known to hold might have been holding a
+                // second copy, so we can no longer be sure what they hold. Nothing that was deduced
+                // about their hand can be relied on either, except that they cannot be holding a
+                // card which would have prevented the play, such as the Countess.
+                Event::PlayCard { player_idx, card } => {
+                    for known in self.knowledge.iter_mut() {
+                        if known[player_idx] == Some(card) {
+                            known[player_idx] = None;
+                        }
+                    }
+                    self.exclusions[player_idx].clear();
+                    for other in self.cards_in_deck().filter(|&other| effects.get(other).restrict(card).is_err()) {
+                        self.exclude_card(player_idx, other);
+                    }
+                    played = Some((player_idx, card));
+                }
+
+                // A guess is about a value, so a missed guess rules out every card with that value.
+                // If a Guard's guess was correct, the target is about to be eliminated anyway, but a
+                // correct guess with the Bishop earns its player a token for that card instead, and
+                // leaves the target's hand alone.
+                Event::Guess { target_idx, guess } if !matches!(events.get(position + 1), Some(&Event::GainToken { player_idx, card }) if Some((player_idx, card)) == played) => {
+                    let value = effects.get(guess).value();
+                    for card in self.cards_in_deck().filter(|&card| effects.get(card).value() == value) {
+                        self.exclude_card(target_idx, card);
+                    }
+                }
+
+                Event::ShowCard { player_idx, target_idx, card } => {
+                    self.knowledge[player_idx][target_idx] = Some(card);
+                }
+
+                // Everybody sees the loser's card when they are eliminated, and knows from the card
+                // played whether the winner's card must be stronger, as with the Baron, or weaker,
+                // as with the Dowager Queen.
+                Event::CompareHands { player_idx, player_card, target_idx, target_card } => {
+                    self.knowledge[player_idx][target_idx] = Some(target_card);
+                    self.knowledge[target_idx][player_idx] = Some(player_card);
+                    let (winner_idx, winning_card, losing_card) = match events.get(position + 1) {
+                        Some(&Event::EliminatePlayer { player_idx: loser_idx, .. }) if loser_idx == player_idx => (target_idx, target_card, player_card),
+                        Some(&Event::EliminatePlayer { player_idx: loser_idx, .. }) if loser_idx == target_idx => (player_idx, player_card, target_card),
+                        _ => continue,
+                    };
+                    let winning = effects.strength_cmp(winning_card, losing_card);
+                    for card in self.cards_in_deck().filter(|&card| effects.strength_cmp(card, losing_card) != winning) {
+                        self.exclude_card(winner_idx, card);
+                    }
+                }
+
+                // Anything known about either hand follows it to its new owner.
+                Event::SwapHands { player_idx, player_card, target_idx, target_card } => {
+                    for known in self.knowledge.iter_mut() {
+                        known.swap(player_idx, target_idx);
+                    }
+                    self.exclusions.swap(player_idx, target_idx);
+                    self.knowledge[player_idx][player_idx] = None;
+                    self.knowledge[target_idx][target_idx] = None;
+                    self.knowledge[player_idx][target_idx] = Some(player_card);
+                    self.knowledge[target_idx][player_idx] = Some(target_card);
+                }
+
+                Event::DiscardCard { target_idx: player_idx, .. } | Event::EliminatePlayer { player_idx, .. } => {
+                    for known in self.knowledge.iter_mut() {
+                        known[player_idx] = None;
+                    }
+                    self.exclusions[player_idx].clear();
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    /// Get each distinct card in the deck for this game, in ascending order.
+    fn cards_in_deck(&self) -> impl Iterator<Item = Card> {
+        let mut cards = self.cards();
+        cards.dedup();
+        cards.into_iter()
+    }
+
+    /// Record that everybody knows the given player not to be holding the given card.
+    fn exclude_card(&mut self, player_idx: usize, card: Card) {
+        if !self.exclusions[player_idx].contains(&card) {
+            self.exclusions[player_idx].push(card);
+        }
     }
 
     /// Get the players who are potential targets for an action.
@@ -418,29 +1402,60 @@ impl Game {
     }
 
     /// Work out whose turn it should be next in the game
-    fn next_player(&self) -> usize {
-        let mut player_idx = (self.turn_counter + 1) % self.players.len();
-        while !self.players[player_idx].active() {
-            player_idx = (self.turn_counter + 1) % self.players.len();
-        }
-        player_idx
+    fn next_player(&self) -> Result<usize, GameError> {
+        let players = self.players.len();
+        (1..=players).map(|offset| (self.turn_counter + offset) % players)
+            .find(|&player_idx| self.players[player_idx].active())
+            .ok_or(GameError::InconsistentState)
     }
 
     /// Given that the deck is empty but no player has won outright, determine the winners
-    fn calculate_winners(&self) -> Vec<usize> {
+    fn calculate_winners(&self) -> Result<(Vec<usize>, WinReason), GameError> {
 
         // Find the players who are still in the game
         let active_players = self.active_players();
 
-        // Calculate each player's effective score, consisting of the card they hold and the total
-        // value of their discarded cards throughout the game
-        let mut scores = active_players.iter().map(|&idx| 
-            (self.players[idx].card().unwrap(), self.players[idx].value_of_discards(), idx)
-        ).collect::<Vec<_>>();
+        // Find each player's score and the total value of their discarded cards
+        let scores = active_players.iter().map(|&idx| {
+            let (card, score, discarded) = self.final_score(idx)?;
+            Ok((card, (score, discarded), idx))
+        }).collect::<Result<Vec<_>, GameError>>()?;
+
+        // A card which loses to another that is still held, as the Bishop does to the Princess,
+        // cannot win however high its score
+        let held = scores.iter().map(|s| s.0).collect::<Vec<_>>();
+        let scores = scores.into_iter()
+            .filter(|s| !held.iter().any(|&other| self.effect(s.0).loses_to(other)))
+            .collect::<Vec<_>>();
+
+        // Return each player who has the highest score, and whether the total value of their
+        // discards was needed to decide between them
+        let high_score = scores.iter().map(|s| s.1).max().ok_or(GameError::InconsistentState)?;
+        let winners = scores.iter().filter(|s| s.1 == high_score).map(|s| s.2).collect::<Vec<_>>();
+        let reason = if winners.len() > 1 {
+            WinReason::Tie
+        } else if scores.iter().filter(|s| s.1.0 == high_score.0).count() > 1 {
+            WinReason::DiscardTotal
+        } else {
+            WinReason::HighestScore
+        };
+        Ok((winners, reason))
+    }
 
-        // Sort the scores and return each player who has the highest score
-        scores.sort(); let high_score = scores[scores.len() - 1];
-        scores.iter().filter(|s| s.0 == high_score.0 && s.1 == high_score.1).map(|s| s.2).collect()
+    /// Get the card held by a player at the end of the round, their score, consisting of the value
+    /// of that card plus any bonus for the cards they have discarded, such as the Count, and the
+    /// total value of their discarded cards throughout the game.
+    ///
+    /// A player whose turn was cut short by everybody else leaving the game may still be holding
+    /// two cards, in which case the one with the higher value counts.
+    fn final_score(&self, player_idx: usize) -> Result<(Card, u32, u32), GameError> {
+        let card = self.players[player_idx].hand().iter().copied()
+            .max_by_key(|&card| (self.effect(card).value(), card))
+            .ok_or(GameError::InconsistentState)?;
+        let discards = || self.players[player_idx].discards();
+        let bonus = discards().map(|c| self.effect(c).discard_bonus()).sum::<u32>();
+        let discarded = discards().map(|c| self.effect(c).value()).sum::<u32>();
+        Ok((card, self.effect(card).value() + bonus, discarded))
     }
 }
 
@@ -450,6 +1465,41 @@ impl Default for Game {
     }
 }
 
+impl PartialEq for Game {
+    fn eq(&self, other: &Self) -> bool {
+        let canonical_players = |game: &Game| game.players.iter().map(|player| (
+            hashing::card_counts(player.hand().iter().copied()),
+            hashing::card_counts(player.discards()),
+            player.protected(),
+            player.active(),
+        )).collect::<Vec<_>>();
+        let canonical_exclusions = |game: &Game| game.exclusions.iter()
+            .map(|cards| hashing::card_counts(cards.iter().copied()))
+            .collect::<Vec<_>>();
+
+        self.state == other.state
+            && self.ruleset == other.ruleset
+            && self.forced_target == other.forced_target
+            && self.marks == other.marks
+            && self.pending_choice == other.pending_choice
+            && self.current_player() == other.current_player()
+            && self.deck.cards() == other.deck.cards()
+            && self.burned_card == other.burned_card
+            && self.knowledge == other.knowledge
+            && canonical_exclusions(self) == canonical_exclusions(other)
+            && hashing::card_counts(self.removed_cards.iter().copied()) == hashing::card_counts(other.removed_cards.iter().copied())
+            && canonical_players(self) == canonical_players(other)
+    }
+}
+
+impl Eq for Game {}
+
+impl Hash for Game {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.zobrist_hash());
+    }
+}
+
 /// An enum representing the possible high-level states of a game of Love Letter.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 enum GameState {
@@ -466,7 +1516,7 @@ enum GameState {
 
 /// An error type representing the possible reasons that a game action might fail.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum GameError {
 
     /// Attempted to start a game with an invalid number of players.
@@ -493,18 +1543,54 @@ pub enum GameError {
     /// Tried to target a protected player.
     CannotTargetProtectedPlayer,
 
+    /// Tried to target the same player twice with a card that has two targets.
+    CannotTargetPlayerTwice(usize),
+
+    /// Did not target the player chosen with the Sycophant, when the card played could have.
+    MustTargetPlayer(usize),
+
     // Tried to target an eliminated player.
     CannotTargetEliminatedPlayer,
 
     // Tried to illegally play the Prince or King while holding the Countess
     CannotPlayWhileHoldingCountess(Card),
+
+    /// Tried to play the Prince or King, or their insane versions, while holding the Mi-Go.
+    CannotPlayWhileHoldingMiGo(Card),
+
+    /// Played a custom card which makes a guess on a target without giving a guess.
+    MustProvideGuess(Card),
+
+    /// Tried to start a game with a custom deck which cannot be used.
+    InvalidDeck(DeckError),
+
+    /// Tried to play a card while the given player has yet to make a choice.
+    ChoicePending(usize),
+
+    /// Tried to make a choice when none was waiting to be made.
+    NoChoicePending,
+
+    /// Tried to make a choice that another player must make.
+    ChoiceOutOfTurn(usize),
+
+    /// Tried to make a choice which is not one of the options.
+    InvalidChoice(Choice),
+
+    /// Tried to forfeit as a player who has already been eliminated.
+    PlayerEliminated(usize),
+
+    /// The game reached a state that should be impossible, such as a player holding the wrong
+    /// number of cards. This indicates a bug in the engine, or a game built from an invalid
+    /// position. The action may have been partly carried out, and the game should not be used
+    /// any further.
+    InconsistentState,
 }
 
 impl fmt::Display for GameError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use GameError::*;
         match self {
-            InvalidNumberOfPlayers(players) => write!(f, "Invalid number of players: {}. There must be between 2 and 4 players in a game.", players),
+            InvalidNumberOfPlayers(players) => write!(f, "Invalid number of players: {}. There must be between 2 and 4 players in a game, or up to 6 in Lovecraft Letter and 8 with the Premium cards.", players),
             GameNotInProgress => write!(f, "No game is in progress."),
             PlayerDoesNotExist(player) => write!(f, "Player {} does not exist.", player),
             PlayedOutOfTurn(player) => write!(f, "It is not Player {}'s turn", player),
@@ -512,8 +1598,19 @@ impl fmt::Display for GameError {
             MustProvideTarget(card) => write!(f, "You must provide a target when playing the {:?}.", card),
             CannotTargetSelf(card) => write!(f, "You cannot target yourself when playing the {:?}.", card),
             CannotTargetProtectedPlayer => write!(f, "You cannot target a protected player."),
+            CannotTargetPlayerTwice(player) => write!(f, "You cannot target Player {} twice.", player),
+            MustTargetPlayer(player) => write!(f, "You must target Player {}, who was chosen with the Sycophant.", player),
             CannotTargetEliminatedPlayer => write!(f, "You cannot target an eliminated player."),
             CannotPlayWhileHoldingCountess(card) => write!(f, "You cannot player the {:?} while holding the Countess", card),
+            CannotPlayWhileHoldingMiGo(card) => write!(f, "You cannot play the {:?} while holding the Mi-Go.", card),
+            MustProvideGuess(card) => write!(f, "You must provide a guess when playing the {:?}.", card),
+            InvalidDeck(err) => write!(f, "Invalid deck: {}", err),
+            ChoicePending(player) => write!(f, "Player {} must make a choice first.", player),
+            NoChoicePending => write!(f, "There is no choice to be made."),
+            ChoiceOutOfTurn(player) => write!(f, "It is not Player {}'s choice to make.", player),
+            InvalidChoice(choice) => write!(f, "{:?} is not one of the options.", choice),
+            PlayerEliminated(player) => write!(f, "Player {} has already been eliminated.", player),
+            InconsistentState => write!(f, "The game is in an inconsistent state."),
         }
     }
 }
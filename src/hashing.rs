@@ -0,0 +1,230 @@
+//! Stable hashing of game states and information sets, for transposition tables and the like.
+//!
+//! Hashes are computed in the style of Zobrist hashing: every feature of a position (for example,
+//! "player 2 holds one Baron" or "the fifth card of the deck is a Guard") is assigned a fixed
+//! pseudo-random 64-bit key, and the hash of the position is the XOR of the keys of all of its
+//! features. The keys are derived from the features themselves rather than from a random seed, so
+//! hashes are stable across runs and platforms.
+//!
+//! Two kinds of position can be hashed. A full game state, via `Game::zobrist_hash`, includes
+//! everything about the game including the order of the deck. An `InformationSet`, obtained from
+//! `Game::information_set`, includes only what a single player can see, so that positions which
+//! that player cannot tell apart hash identically.
+//!
+//! In both cases, the order in which cards were discarded is not taken into account, nor is the
+//! order of the cards within a player's hand.
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::card::Card;
+use crate::choice::{ChoiceKind, PendingChoice};
+use crate::effect::Mark;
+use crate::observation::Observation;
+use crate::ruleset::Ruleset;
+
+/// The kinds of feature that make up a position.
+#[derive(Copy, Clone)]
+pub(crate) enum Feature {
+    State,
+    Players,
+    Observer,
+    Turn,
+    DeckCard,
+    DeckSize,
+    BurnedCard,
+    HandCount,
+    DiscardCount,
+    Protected,
+    Active,
+    KnownCard,
+    ExcludedCard,
+    RemovedCount,
+    Ruleset,
+    ForcedTarget,
+    Mark,
+    Choice,
+}
+
+/// The key for a feature with the given parameters.
+pub(crate) fn key(feature: Feature, a: usize, b: usize, c: usize) -> u64 {
+    let input = (feature as u64) << 48 ^ (a as u64) << 32 ^ (b as u64) << 16 ^ c as u64;
+
+    // The SplitMix64 finaliser, which spreads each input across all 64 bits.
+    let mut z = input.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Count the number of each card among the given cards, indexed by the number of the card (which
+/// for the classic cards is also its value). The counts stop at the highest-numbered card present,
+/// so that equal collections of cards always have equal counts.
+pub(crate) fn card_counts(cards: impl IntoIterator<Item = Card>) -> Vec<u8> {
+    let mut counts = Vec::new();
+    for card in cards {
+        if counts.len() <= card.number() {
+            counts.resize(card.number() + 1, 0);
+        }
+        counts[card.number()] += 1;
+    }
+    counts
+}
+
+/// Everything that a single player can see about a game, in a canonical form suitable for use as a
+/// key in a transposition table or a table of regrets.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct InformationSet {
+
+    /// The player to whom this information set belongs.
+    pub player_idx: usize,
+
+    /// The rules under which the game is being played.
+    pub ruleset: Ruleset,
+
+    /// The player whose turn it is, or `None` if the game is not in progress.
+    pub current_player: Option<usize>,
+
+    /// The cards held in this player's hand, in ascending order.
+    pub hand: Vec<Card>,
+
+    /// For each player, the number of each card that they have discarded, indexed by card number.
+    pub discards: Vec<Vec<u8>>,
+
+    /// Whether each player is currently protected by the Handmaid.
+    pub protected: Vec<bool>,
+
+    /// Whether each player is still in the game.
+    pub active: Vec<bool>,
+
+    /// The number of cards remaining in the deck.
+    pub deck_size: usize,
+
+    /// The cards that this player knows to be held by each of the others.
+    pub known_cards: Vec<Option<Card>>,
+
+    /// For each player, the cards which everybody knows them not to be holding, in ascending order.
+    pub excluded_cards: Vec<Vec<Card>>,
+
+    /// The number of each card publicly removed from the game, indexed by card number.
+    pub removed_cards: Vec<u8>,
+
+    /// The player chosen with a card such as the Sycophant, if their choice is still in effect.
+    pub forced_target: Option<usize>,
+
+    /// The choices made this round with cards whose effect comes at the end of the round, in the
+    /// order they were made.
+    pub marks: Vec<Mark>,
+
+    /// The choice which the card being played is waiting for, if any.
+    pub pending_choice: Option<PendingChoice>,
+}
+
+impl InformationSet {
+
+    /// Compute a stable Zobrist-style hash of this information set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::Action;
+    /// # use love_letter::game::Game;
+    /// // The same hand in differently-shuffled games looks the same to the player holding it.
+    /// let hands = (0..100).map(|seed| {
+    ///     let mut game = Game::with_seed(seed);
+    ///     game.perform_action(&Action::StartGame { players: 3 }).unwrap();
+    ///     (game.observation(1).hand, game.information_set(1).zobrist_hash())
+    /// }).collect::<Vec<_>>();
+    /// for (hand, hash) in &hands {
+    ///     for (other_hand, other_hash) in &hands {
+    ///         assert_eq!(hand == other_hand, hash == other_hash);
+    ///     }
+    /// }
+    /// ```
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = key(Feature::Players, self.discards.len(), 0, 0)
+            ^ key(Feature::Observer, self.player_idx, 0, 0)
+            ^ key(Feature::DeckSize, self.deck_size, 0, 0);
+
+        if let Some(player_idx) = self.current_player {
+            hash ^= key(Feature::Turn, player_idx, 0, 0);
+        }
+        for (value, &count) in card_counts(self.hand.iter().copied()).iter().enumerate().filter(|(_, &count)| count > 0) {
+            hash ^= key(Feature::HandCount, self.player_idx, value, count as usize);
+        }
+        for (player_idx, counts) in self.discards.iter().enumerate() {
+            for (value, &count) in counts.iter().enumerate().filter(|(_, &count)| count > 0) {
+                hash ^= key(Feature::DiscardCount, player_idx, value, count as usize);
+            }
+            if self.protected[player_idx] {
+                hash ^= key(Feature::Protected, player_idx, 0, 0);
+            }
+            if self.active[player_idx] {
+                hash ^= key(Feature::Active, player_idx, 0, 0);
+            }
+            if let Some(card) = self.known_cards[player_idx] {
+                hash ^= key(Feature::KnownCard, self.player_idx, player_idx, card.number());
+            }
+            for &card in &self.excluded_cards[player_idx] {
+                hash ^= key(Feature::ExcludedCard, player_idx, card.number(), 0);
+            }
+        }
+        for (value, &count) in self.removed_cards.iter().enumerate().filter(|(_, &count)| count > 0) {
+            hash ^= key(Feature::RemovedCount, value, count as usize, 0);
+        }
+        hash ^= rules_hash(self.ruleset, self.forced_target, &self.marks, self.pending_choice.as_ref());
+
+        hash
+    }
+}
+
+/// The part of a hash describing the ruleset, the lasting effects of cards such as the Sycophant
+/// and Jester, and any choice waiting to be made. This is zero for a classic game, so that classic
+/// hashes are unaffected by the Premium edition.
+pub(crate) fn rules_hash(ruleset: Ruleset, forced_target: Option<usize>, marks: &[Mark], pending_choice: Option<&PendingChoice>) -> u64 {
+    let mut hash = 0;
+    if ruleset != Ruleset::Classic {
+        hash ^= key(Feature::Ruleset, ruleset as usize, 0, 0);
+    }
+    if let Some(target_idx) = forced_target {
+        hash ^= key(Feature::ForcedTarget, target_idx, 0, 0);
+    }
+    for mark in marks {
+        hash ^= key(Feature::Mark, mark.card.number(), mark.player_idx, mark.target_idx);
+    }
+    if let Some(pending) = pending_choice {
+        hash ^= match pending.kind {
+            ChoiceKind::LookAtHand { first_idx, second_idx } => key(Feature::Choice, pending.player_idx, first_idx + 1, second_idx + 1),
+            ChoiceKind::Redraw => key(Feature::Choice, pending.player_idx, 0, 0),
+        };
+    }
+    hash
+}
+
+impl From<&Observation> for InformationSet {
+    fn from(observation: &Observation) -> Self {
+        let mut hand = observation.hand.clone();
+        hand.sort();
+        Self {
+            player_idx: observation.player_idx,
+            ruleset: observation.ruleset,
+            current_player: observation.current_player,
+            hand,
+            discards: (0..observation.num_players).map(|idx| card_counts(observation.discards(idx))).collect(),
+            protected: observation.protected.clone(),
+            active: observation.active.clone(),
+            deck_size: observation.deck_size,
+            known_cards: observation.known_cards.clone(),
+            excluded_cards: observation.excluded_cards.iter().map(|cards| {
+                let mut cards = cards.clone();
+                cards.sort();
+                cards
+            }).collect(),
+            removed_cards: card_counts(observation.removed_cards.iter().copied()),
+            forced_target: observation.forced_target,
+            marks: observation.marks.clone(),
+            pending_choice: observation.pending_choice,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! A record of the cards that each player has discarded, and of how each one left their hand.
+//!
+//! A player's discards are made up of the cards they chose to play, the cards other players' cards
+//! forced them to discard, such as with the Prince, and the card revealed from their hand when they
+//! were eliminated. In Lovecraft Letter, the cards revealed from the top of the deck in insanity
+//! checks are added to their discards as well, without ever having been in their hand. Every one
+//! of these counts towards the total value of their discards at the end of the round, but only the
+//! cards they played say anything about the choices they made.
+//!
+//! The engine itself has no need of the distinction. Scoring counts every discard alike, as the
+//! rules require, and what each player knows about the others' hands is deduced from the events
+//! of each play rather than from their discards. It is recorded for agents estimating what their
+//! opponents hold, and for interfaces showing each player's discard pile.
+//!
+//! ```
+//! # use love_letter::action::{Action, PlayCardDetails};
+//! # use love_letter::builder::GameBuilder;
+//! # use love_letter::card::Card;
+//! # use love_letter::history::{Discard, DiscardKind};
+//! use Card::*;
+//!
+//! let mut game = GameBuilder::new(3)
+//!     .hand(0, &[Prince, Guard])
+//!     .hand(1, &[Princess])
+//!     .hand(2, &[Baron])
+//!     .deck(&[Guard, Guard, Guard, Priest, Priest, Baron, Handmaid, Handmaid, Prince, King, Countess])
+//!     .burned_card(Some(Guard))
+//!     .build()
+//!     .unwrap();
+//! let details = PlayCardDetails::PlayPrince { target_idx: 1 };
+//! game.perform_action(&Action::PlayCard { player_idx: 0, details }).unwrap();
+//!
+//! let history = game.observation(2).history;
+//! assert_eq!(history[0], vec![Discard { card: Prince, kind: DiscardKind::Played }]);
+//! assert_eq!(history[1], vec![Discard { card: Princess, kind: DiscardKind::Forced }]);
+//! ```
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::card::Card;
+
+/// How a card came to be among a player's discards.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum DiscardKind {
+
+    /// The player played the card from their hand.
+    Played,
+
+    /// The player was made to discard the card from their hand by the effect of a card, such as
+    /// the Prince.
+    Forced,
+
+    /// The player revealed the card in their hand on being eliminated.
+    Revealed,
+
+    /// The card was revealed from the top of the deck in an insanity check.
+    InsanityCheck,
+}
+
+/// A single card among a player's discards.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Discard {
+
+    /// The card discarded.
+    pub card: Card,
+
+    /// How the card came to be discarded.
+    pub kind: DiscardKind,
+}
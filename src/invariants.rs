@@ -0,0 +1,133 @@
+//! Consistency checks on the state of a game.
+//!
+//! However a game is played, some things should always be true of it: no card is ever created or
+//! destroyed, and every player holds the right number of cards for the stage of their turn. These
+//! invariants are checked after every action in debug builds, and may be checked at any time using
+//! `Game::check_invariants`, for example by clients and fuzzers wanting to be sure that the engine
+//! is behaving.
+
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::card::Card;
+use crate::game::Game;
+
+/// A way in which the state of a game is inconsistent.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum InvariantViolation {
+
+    /// A card appears a different number of times across the deck, the hands, the discards, the
+    /// burned card and the cards removed from the game than it does in a full deck for the ruleset.
+    CardsNotConserved { card: Card, expected: usize, actual: usize },
+
+    /// A player is holding the wrong number of cards.
+    WrongNumberOfCardsInHand { player_idx: usize, expected: usize, actual: usize },
+
+    /// The player whose turn it is has been eliminated.
+    CurrentPlayerEliminated(usize),
+
+    /// The player whose turn it is is still protected by a Handmaid played on their last turn.
+    CurrentPlayerProtected(usize),
+
+    /// The game is in progress, but fewer than two players remain in it.
+    NotEnoughActivePlayers,
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use InvariantViolation::*;
+        match self {
+            CardsNotConserved { card, expected, actual } => write!(f, "There are {} copies of the {:?} but there should be {}.", actual, card, expected),
+            WrongNumberOfCardsInHand { player_idx, expected, actual } => write!(f, "Player {} holds {} cards but should hold {}.", player_idx, actual, expected),
+            CurrentPlayerEliminated(player_idx) => write!(f, "Player {} is to play but has been eliminated.", player_idx),
+            CurrentPlayerProtected(player_idx) => write!(f, "Player {} is to play but is still protected.", player_idx),
+            NotEnoughActivePlayers => write!(f, "Fewer than two players remain in the game."),
+        }
+    }
+}
+
+impl Error for InvariantViolation {}
+
+impl Game {
+
+    /// Check that the state of the game is consistent, returning the first problem found if not.
+    ///
+    /// Once a game has started, every card must be accounted for exactly once, eliminated players
+    /// must hold no cards and every other player must hold one card - except for the player whose
+    /// turn it is, who must hold two, must not have been eliminated and must no longer be
+    /// protected. While the game is in progress, at least two players must remain in it. A game
+    /// which ended when everybody else left part way through a player's turn may leave that player
+    /// holding two cards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::action::Action;
+    /// # use love_letter::game::Game;
+    /// let mut game = Game::new();
+    /// game.perform_action(&Action::StartGame { players: 4 }).unwrap();
+    /// while let Some(player_idx) = game.current_player() {
+    ///     assert_eq!(game.check_invariants(), Ok(()));
+    ///     let details = game.legal_plays(player_idx)[0].clone();
+    ///     game.perform_action(&Action::PlayCard { player_idx, details }).unwrap();
+    /// }
+    /// assert_eq!(game.check_invariants(), Ok(()));
+    /// ```
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        if !self.has_started() {
+            return Ok(());
+        }
+        let players = self.players();
+        let current_player = self.current_player();
+
+        // Check that every card is accounted for exactly once.
+        let all_cards = players.iter().flat_map(|player| player.hand().iter().chain(player.history().iter().map(|discard| &discard.card)))
+            .chain(self.deck_cards().iter())
+            .chain(self.burned_card().iter())
+            .chain(self.removed_cards().iter())
+            .copied().collect::<Vec<_>>();
+        let expected_cards = self.cards();
+        let mut cards = expected_cards.iter().chain(all_cards.iter()).copied().collect::<Vec<_>>();
+        cards.sort();
+        cards.dedup();
+        for card in cards {
+            let count = |cards: &[Card]| cards.iter().filter(|&&c| c == card).count();
+            let (expected, actual) = (count(&expected_cards), count(&all_cards));
+            if actual != expected {
+                return Err(InvariantViolation::CardsNotConserved { card, expected, actual });
+            }
+        }
+
+        // Check the state of the player whose turn it is.
+        if let Some(player_idx) = current_player {
+            if !players[player_idx].active() {
+                return Err(InvariantViolation::CurrentPlayerEliminated(player_idx));
+            }
+            if players[player_idx].protected() {
+                return Err(InvariantViolation::CurrentPlayerProtected(player_idx));
+            }
+            if players.iter().filter(|player| player.active()).count() < 2 {
+                return Err(InvariantViolation::NotEnoughActivePlayers);
+            }
+        }
+
+        // Check that each player is holding the right number of cards.
+        for (player_idx, player) in players.iter().enumerate() {
+            let expected = match current_player {
+                _ if !player.active() => 0,
+                Some(current_player) if current_player == player_idx => 2,
+                None if self.is_over() && player_idx == self.turn_counter() && player.hand().len() == 2 => 2,
+                _ => 1,
+            };
+            if player.hand().len() != expected {
+                return Err(InvariantViolation::WrongNumberOfCardsInHand { player_idx, expected, actual: player.hand().len() });
+            }
+        }
+
+        Ok(())
+    }
+}
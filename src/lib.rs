@@ -1,6 +1,27 @@
 pub mod action;
+pub mod agent;
+pub mod builder;
 pub mod card;
+pub mod chance;
+pub mod choice;
+pub mod compact;
+pub mod custom;
 mod deck;
+pub mod effect;
+pub mod encoding;
+pub mod environment;
 pub mod event;
+pub mod fuzz;
 pub mod game;
+pub mod hashing;
+pub mod history;
+pub mod invariants;
+pub mod observation;
 mod player;
+pub mod protocol;
+pub mod ruleset;
+#[cfg(feature = "scenarios")]
+pub mod scenario;
+pub mod simulation;
+pub mod solver;
+pub mod tournament;
@@ -0,0 +1,78 @@
+//! A view of a game of Love Letter from the perspective of a single player.
+//!
+//! An `Observation` contains only the information that the player is entitled to see - their own
+//! hand, and the public state of the table - and so can safely be handed to an agent deciding
+//! what to play.
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::card::Card;
+use crate::choice::PendingChoice;
+use crate::effect::Mark;
+use crate::history::Discard;
+use crate::ruleset::Ruleset;
+
+/// Everything that a single player can see about a game of Love Letter.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Observation {
+
+    /// The player to whom this observation belongs.
+    pub player_idx: usize,
+
+    /// The rules under which the game is being played.
+    pub ruleset: Ruleset,
+
+    /// The number of players in the game.
+    pub num_players: usize,
+
+    /// The player whose turn it is, or `None` if the game is not in progress.
+    pub current_player: Option<usize>,
+
+    /// The cards held in this player's hand.
+    pub hand: Vec<Card>,
+
+    /// The cards discarded by each player along with how each one was discarded, such as being
+    /// played or revealed on elimination, in the order they were discarded.
+    pub history: Vec<Vec<Discard>>,
+
+    /// Whether each player is currently protected by the Handmaid.
+    pub protected: Vec<bool>,
+
+    /// Whether each player is still in the game.
+    pub active: Vec<bool>,
+
+    /// The number of cards remaining in the deck.
+    pub deck_size: usize,
+
+    /// The cards that this player knows to be held by each of the others, having seen them with
+    /// the Priest, Baron or King.
+    pub known_cards: Vec<Option<Card>>,
+
+    /// The cards that each player is known by everybody not to be holding, having been deduced
+    /// from missed Guard guesses, Baron comparisons and the restriction imposed by the Countess.
+    pub excluded_cards: Vec<Vec<Card>>,
+
+    /// The cards publicly removed from the game at the start of a two-player game.
+    pub removed_cards: Vec<Card>,
+
+    /// The player chosen with a card such as the Sycophant, who must be targeted by the next card
+    /// played if possible.
+    pub forced_target: Option<usize>,
+
+    /// The choices made this round with cards whose effect comes at the end of the round, such as
+    /// the Jester.
+    pub marks: Vec<Mark>,
+
+    /// The choice which the card being played is waiting for, if any.
+    pub pending_choice: Option<PendingChoice>,
+}
+
+impl Observation {
+
+    /// Get the cards discarded by the given player, in the order they were discarded.
+    pub fn discards(&self, player_idx: usize) -> impl Iterator<Item = Card> + '_ {
+        self.history[player_idx].iter().map(|discard| discard.card)
+    }
+}
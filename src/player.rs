@@ -1,6 +1,7 @@
 //! A representation of a single player in a game of Love Letter.
 
 use crate::card::Card;
+use crate::history::{Discard, DiscardKind};
 
 /// A player in a game of Love Letter.
 #[derive(Clone, Debug)]
@@ -9,8 +10,9 @@ pub struct Player {
     /// The cards currently held in this player's hand.
     hand: Vec<Card>,
 
-    /// The cards that have previously been discarded by this player.
-    discards: Vec<Card>,
+    /// The cards that have previously been discarded by this player, and how each one was
+    /// discarded.
+    discards: Vec<Discard>,
 
     /// Whether this player is currently protected by the effect of the Handmaid.
     protected: bool,
@@ -36,12 +38,28 @@ impl Player {
         self.hand.push(card);
     }
 
+    /// Replace the cards held in this player's hand.
+    pub fn set_hand(&mut self, hand: Vec<Card>) {
+        self.hand = hand;
+    }
+
+    /// Replace the cards that this player has discarded, all of which are taken to have been
+    /// played.
+    pub fn set_discards(&mut self, discards: Vec<Card>) {
+        self.discards = discards.into_iter().map(|card| Discard { card, kind: DiscardKind::Played }).collect();
+    }
+
+    /// Add a card to this player's discards.
+    pub fn add_discard(&mut self, card: Card, kind: DiscardKind) {
+        self.discards.push(Discard { card, kind });
+    }
+
     /// Take the specified card from this player.
     pub fn play_card(&mut self, card: Card) -> Result<(), ()> {
         match self.hand.iter().position(|&c| c == card) {
             Some(index) => {
                 self.hand.remove(index);
-                self.discards.push(card);
+                self.add_discard(card, DiscardKind::Played);
                 Ok(())
             }
             None => Err(()),
@@ -55,22 +73,53 @@ impl Player {
 
     /// Get the single card that this player is holding.
     pub fn card(&self) -> Option<Card> {
-        if self.hand.len() == 1 { self.hand.first().map(|c| *c) } else { None }
+        if self.hand.len() == 1 { self.hand.first().copied() } else { None }
     }
 
-    /// Get the single card that this player is holding and remove it from their hand.
-    pub fn take_card(&mut self) -> Option<Card> {
+    /// Get the single card that this player is holding and move it to their discards.
+    pub fn take_card(&mut self, kind: DiscardKind) -> Option<Card> {
         let card = self.card();
-        if let Some(card) = card { 
-            self.discards.push(card);
+        if let Some(card) = card {
+            self.add_discard(card, kind);
             self.hand.clear();
         }
         card
     }
 
-    /// Get the total value of the cards this player has discarded
-    pub fn value_of_discards(&self) -> u32 {
-        self.discards.iter().map(|&c| c as u32).sum()
+    /// Take every card that this player is holding, in ascending order, and move them to their
+    /// discards.
+    pub fn take_hand(&mut self, kind: DiscardKind) -> Vec<Card> {
+        let mut hand = std::mem::take(&mut self.hand);
+        hand.sort();
+        for &card in &hand {
+            self.add_discard(card, kind);
+        }
+        hand
+    }
+
+    /// Replace the single card that this player is holding with another, returning the original.
+    pub fn exchange_card(&mut self, card: Card) -> Option<Card> {
+        let original = self.card();
+        if original.is_some() {
+            self.hand[0] = card;
+        }
+        original
+    }
+
+    /// Get the cards currently held in this player's hand.
+    pub fn hand(&self) -> &[Card] {
+        &self.hand
+    }
+
+    /// Get the cards that this player has discarded, in the order they were discarded.
+    pub fn discards(&self) -> impl Iterator<Item = Card> + '_ {
+        self.discards.iter().map(|discard| discard.card)
+    }
+
+    /// Get the cards that this player has discarded along with how each one was discarded, in the
+    /// order they were discarded.
+    pub fn history(&self) -> &[Discard] {
+        &self.discards
     }
 
     /// Check if this player is currently protected by a Handmaid.
@@ -0,0 +1,388 @@
+//! A line-based text protocol allowing bots written in any language to play against the engine.
+//!
+//! A bot is an executable which reads messages from its standard input and writes replies to its
+//! standard output, one per line. Cards are written by name (`Guard`, `Priest`, ..., `Princess`),
+//! players by their seat number starting from 0, and words are separated by single spaces.
+//!
+//! The engine sends the following messages:
+//!
+//!   - `loveletter 1` - sent once when the bot starts. The bot may reply with `name <name>` to
+//!     give itself a name, and must then reply with `ok`.
+//!   - `newgame <players> <seat>` - a new game is beginning, in which the bot occupies `<seat>`.
+//!   - `event <event>` - something the bot is entitled to see has happened. See below.
+//!   - `legal <play>` - one of the plays the bot may make, sent once per legal play before `go`.
+//!   - `go` - it is the bot's turn. The bot must reply with `play <play>`.
+//!   - `quit` - the bot should exit.
+//!
+//! A `<play>` consists of the card being played, followed by the targets' seats for cards with
+//! targets, followed by the guess when playing a Guard, Bishop or Deep Ones - for example
+//! `Guard 2 Baron`, `Prince 0`, `Cardinal 1 3` or `Handmaid`. If a card with a target is played
+//! when nobody can be targeted, the targets (and guess) are omitted. Card names are written
+//! without spaces, as in `DowagerQueen`.
+//!
+//! Events are written as a lowercase name followed by their details:
+//!
+//!   - `newgame <players>`, `register <player>`, `burn`, `remove <card>`
+//!   - `deal <player> <card>`, `ready <player>`, `endturn <player>`, `play <player> <card>`
+//!   - `notarget <player> <card>`, `guess <target> <card>`, `show <player> <target> <card>`
+//!   - `compare <player> <card> <target> <card>`, `swap <player> <card> <target> <card>`
+//!   - `discard <target> <card>`, `eliminate <player> <reason>`, `reveal <player> <card>`, where
+//!     the reason is one of `guessed <player>`, `compared <player>`, `discarded <player> <card>`,
+//!     `played <card>`, `insane <card>`, `effect <player> <card>`, `timeout` or `forfeit`
+//!   - `protect <player>`, `unprotect <player>`
+//!   - `check <player> <card>`, `target <player> <target>`, `token <player> <card>`
+//!   - `choose <player> look <target> <target>`, `choose <player> redraw`
+//!   - `final <player> <card> <score> <discard total>`, `decided <reason>`, where the reason is
+//!     one of `laststanding`, `effect`, `score`, `discards` or `tie`
+//!   - `gameover [<winner> ...]`
+//!
+//! Bots are only sent the events they are entitled to see - for example, they are not told which
+//! cards are dealt to other players. Bots are not asked to make the choices described in the
+//! `choice` module, which are made on their behalf.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::action::PlayCardDetails;
+use crate::agent::Agent;
+use crate::card::Card;
+use crate::choice::ChoiceKind;
+use crate::event::{EliminationReason, Event, WinReason};
+use crate::observation::Observation;
+
+/// The version of the protocol spoken by this engine.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The time a bot is given to reply to each message, unless otherwise specified.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Format an event as it is sent to bots, without the leading `event`.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::card::Card;
+/// # use love_letter::event::Event;
+/// # use love_letter::protocol;
+/// let event = Event::ShowCard { player_idx: 0, target_idx: 2, card: Card::King };
+/// assert_eq!(protocol::format_event(&event), "show 0 2 King");
+/// ```
+pub fn format_event(event: &Event) -> String {
+    use Event::*;
+    match event {
+        NewGame { players } => format!("newgame {}", players),
+        RegisterPlayer { player_idx } => format!("register {}", player_idx),
+        BurnCard {} => "burn".to_string(),
+        RemoveCardFromGame { card } => format!("remove {:?}", card),
+        DealCard { player_idx, card } => format!("deal {} {:?}", player_idx, card),
+        ReadyToPlay { player_idx } => format!("ready {}", player_idx),
+        TurnEnded { player_idx } => format!("endturn {}", player_idx),
+        PlayCard { player_idx, card } => format!("play {} {:?}", player_idx, card),
+        NoValidTarget { player_idx, card } => format!("notarget {} {:?}", player_idx, card),
+        Guess { target_idx, guess } => format!("guess {} {:?}", target_idx, guess),
+        ShowCard { player_idx, target_idx, card } => format!("show {} {} {:?}", player_idx, target_idx, card),
+        CompareHands { player_idx, player_card, target_idx, target_card } =>
+            format!("compare {} {:?} {} {:?}", player_idx, player_card, target_idx, target_card),
+        DiscardCard { target_idx, card } => format!("discard {} {:?}", target_idx, card),
+        SwapHands { player_idx, player_card, target_idx, target_card } =>
+            format!("swap {} {:?} {} {:?}", player_idx, player_card, target_idx, target_card),
+        EliminatePlayer { player_idx, reason } => format!("eliminate {} {}", player_idx, match reason {
+            EliminationReason::Guessed { by_idx } => format!("guessed {}", by_idx),
+            EliminationReason::LostComparison { by_idx } => format!("compared {}", by_idx),
+            EliminationReason::Discarded { by_idx, card } => format!("discarded {} {:?}", by_idx, card),
+            EliminationReason::Played { card } => format!("played {:?}", card),
+            EliminationReason::InsanityCheck { card } => format!("insane {:?}", card),
+            EliminationReason::CardEffect { by_idx, card } => format!("effect {} {:?}", by_idx, card),
+            EliminationReason::Timeout => "timeout".to_string(),
+            EliminationReason::Forfeit => "forfeit".to_string(),
+        }),
+        RevealCard { player_idx, card } => format!("reveal {} {:?}", player_idx, card),
+        ProtectionGranted { player_idx } => format!("protect {}", player_idx),
+        ProtectionExpired { player_idx } => format!("unprotect {}", player_idx),
+        InsanityCheck { player_idx, card } => format!("check {} {:?}", player_idx, card),
+        ChooseTarget { player_idx, target_idx } => format!("target {} {}", player_idx, target_idx),
+        ChoiceRequired { player_idx, kind: ChoiceKind::LookAtHand { first_idx, second_idx } } =>
+            format!("choose {} look {} {}", player_idx, first_idx, second_idx),
+        ChoiceRequired { player_idx, kind: ChoiceKind::Redraw } => format!("choose {} redraw", player_idx),
+        FinalHand { player_idx, card, score, discard_total } =>
+            format!("final {} {:?} {} {}", player_idx, card, score, discard_total),
+        WinnersDecided { reason } => format!("decided {}", match reason {
+            WinReason::LastPlayerStanding => "laststanding",
+            WinReason::CardEffect => "effect",
+            WinReason::HighestScore => "score",
+            WinReason::DiscardTotal => "discards",
+            WinReason::Tie => "tie",
+        }),
+        GainToken { player_idx, card } => format!("token {} {:?}", player_idx, card),
+        GameOver { winner_indices } => {
+            let winners = winner_indices.iter().map(|idx| idx.to_string()).collect::<Vec<_>>();
+            format!("gameover {}", winners.join(" "))
+        }
+    }
+}
+
+/// Format a play as it is sent to and received from bots.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::action::PlayCardDetails;
+/// # use love_letter::card::Card;
+/// # use love_letter::protocol;
+/// let details = PlayCardDetails::PlayGuard { target_idx: Some(2), guess: Card::Baron };
+/// assert_eq!(protocol::format_play(&details), "Guard 2 Baron");
+/// assert_eq!(protocol::format_play(&PlayCardDetails::PlayHandmaid {}), "Handmaid");
+/// ```
+pub fn format_play(details: &PlayCardDetails) -> String {
+    let mut words = vec![format!("{:?}", details.card())];
+    let targets = details.targets();
+    words.extend(targets.iter().map(|target_idx| target_idx.to_string()));
+    if let Some(guess) = details.guess().filter(|_| !targets.is_empty()) {
+        words.push(format!("{:?}", guess));
+    }
+    words.join(" ")
+}
+
+/// Parse a play in the format sent by bots, returning `None` if it is malformed.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::action::PlayCardDetails;
+/// # use love_letter::protocol;
+/// assert_eq!(protocol::parse_play("Prince 0"), Some(PlayCardDetails::PlayPrince { target_idx: 0 }));
+/// assert_eq!(protocol::parse_play("Baroness 1 2"), Some(PlayCardDetails::PlayBaroness { target_idx: Some(1), second_target_idx: Some(2) }));
+/// assert_eq!(protocol::parse_play("Prince"), None);
+/// ```
+pub fn parse_play(text: &str) -> Option<PlayCardDetails> {
+    let mut words = text.split_whitespace().peekable();
+    let card = words.next()?.parse::<Card>().ok()?;
+    let mut targets = Vec::new();
+    while let Some(target_idx) = words.peek().and_then(|word| word.parse::<usize>().ok()) {
+        targets.push(target_idx);
+        words.next();
+    }
+    let guess = match words.next() {
+        Some(word) => Some(word.parse::<Card>().ok()?),
+        None => None,
+    };
+    if words.next().is_some() {
+        return None;
+    }
+    PlayCardDetails::from_targets(card, &targets, guess)
+}
+
+/// A problem with a bot's behaviour, which caused the engine to make a play on its behalf.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum BotFault {
+
+    /// The bot did not reply within the time allowed.
+    Timeout,
+
+    /// The bot closed its output, or could not be written to.
+    Disconnected,
+
+    /// The bot sent a reply that could not be understood.
+    InvalidReply(String),
+
+    /// The bot chose a play which was not legal.
+    IllegalPlay(PlayCardDetails),
+}
+
+impl fmt::Display for BotFault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use BotFault::*;
+        match self {
+            Timeout => write!(f, "The bot did not reply in time."),
+            Disconnected => write!(f, "The bot disconnected."),
+            InvalidReply(reply) => write!(f, "The bot sent an invalid reply: {}", reply),
+            IllegalPlay(details) => write!(f, "The bot chose an illegal play: {}", format_play(details)),
+        }
+    }
+}
+
+/// An agent backed by a bot running in a separate process, speaking the protocol described in the
+/// module documentation.
+///
+/// If the bot fails to reply in time, or replies with something that isn't a legal play, then the
+/// first legal play is made on its behalf and the fault is recorded. Once the bot has exited or
+/// disconnected, it is no longer connected, and forfeits any game played with
+/// `PlayOptions::forfeit_on_disconnect`. With `PlayOptions::eliminate_on_timeout`, a bot which
+/// fails to reply in time is eliminated instead.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use love_letter::agent::{self, Agent, RandomAgent};
+/// # use love_letter::game::Game;
+/// # use love_letter::protocol::SubprocessAgent;
+/// // A bot which always makes the first legal play it is offered.
+/// let script = r#"
+///     while read -r line; do
+///         case "$line" in
+///             loveletter*) echo "name first-legal"; echo ok ;;
+///             "legal "*) [ -z "$play" ] && play="${line#legal }" ;;
+///             go) echo "play $play"; play="" ;;
+///             quit) exit 0 ;;
+///         esac
+///     done
+/// "#;
+/// let bot = SubprocessAgent::spawn("sh", &["-c", script], Duration::from_secs(5)).unwrap();
+/// assert_eq!(bot.name(), "first-legal");
+///
+/// let mut agents: Vec<Box<dyn Agent>> = vec![Box::new(bot), Box::new(RandomAgent::new(0))];
+/// agent::play_game(&mut Game::with_seed(0), &mut agents).unwrap();
+/// ```
+pub struct SubprocessAgent {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    replies: Receiver<String>,
+    timeout: Duration,
+    faults: Vec<BotFault>,
+    timed_out: bool,
+}
+
+impl SubprocessAgent {
+
+    /// Start the bot with the given command and arguments, and perform the initial handshake. The
+    /// timeout applies both to the handshake as a whole and to each subsequent move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::ErrorKind;
+    /// # use std::time::Duration;
+    /// # use love_letter::protocol::SubprocessAgent;
+    /// // A bot which keeps talking but never finishes the handshake.
+    /// let script = "while true; do echo hello; sleep 0.01; done";
+    /// let result = SubprocessAgent::spawn("sh", &["-c", script], Duration::from_secs(1));
+    /// assert_eq!(result.err().map(|err| err.kind()), Some(ErrorKind::TimedOut));
+    /// ```
+    pub fn spawn(program: &str, args: &[&str], timeout: Duration) -> io::Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        // Read the bot's replies on a separate thread, so that we can stop waiting for them.
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => if sender.send(line).is_err() { break },
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let mut agent = Self { name: program.to_string(), child, stdin, replies, timeout, faults: Vec::new(), timed_out: false };
+        agent.send(&format!("loveletter {}", PROTOCOL_VERSION))?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            match agent.replies.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(line) if line.trim() == "ok" => break,
+                Ok(line) => if let Some(name) = line.trim().strip_prefix("name ") {
+                    agent.name = name.to_string();
+                },
+                Err(RecvTimeoutError::Timeout) => return Err(io::Error::new(io::ErrorKind::TimedOut, "bot did not complete handshake")),
+                Err(RecvTimeoutError::Disconnected) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "bot exited during handshake")),
+            }
+        }
+
+        Ok(agent)
+    }
+
+    /// The faults committed by the bot so far.
+    pub fn faults(&self) -> &[BotFault] {
+        &self.faults
+    }
+
+    /// Send a single line to the bot.
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{}", line)?;
+        self.stdin.flush()
+    }
+
+    /// Ask the bot for its play, returning the fault committed if it fails to make a legal one.
+    fn request_play(&mut self, legal_plays: &[PlayCardDetails]) -> Result<PlayCardDetails, BotFault> {
+
+        // Discard any late replies to earlier requests.
+        while self.replies.try_recv().is_ok() {}
+
+        for details in legal_plays {
+            self.send(&format!("legal {}", format_play(details))).map_err(|_| BotFault::Disconnected)?;
+        }
+        self.send("go").map_err(|_| BotFault::Disconnected)?;
+
+        let reply = match self.replies.recv_timeout(self.timeout) {
+            Ok(reply) => reply,
+            Err(RecvTimeoutError::Timeout) => return Err(BotFault::Timeout),
+            Err(RecvTimeoutError::Disconnected) => return Err(BotFault::Disconnected),
+        };
+
+        let details = reply.trim().strip_prefix("play ")
+            .and_then(parse_play)
+            .ok_or_else(|| BotFault::InvalidReply(reply.clone()))?;
+        if legal_plays.contains(&details) { Ok(details) } else { Err(BotFault::IllegalPlay(details)) }
+    }
+}
+
+impl Agent for SubprocessAgent {
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn new_game(&mut self, players: usize, seat: usize) {
+        let _ = self.send(&format!("newgame {} {}", players, seat));
+    }
+
+    fn notify(&mut self, event: &Event) {
+        let _ = self.send(&format!("event {}", format_event(event)));
+    }
+
+    fn choose_play(&mut self, _observation: &Observation, legal_plays: &[PlayCardDetails]) -> PlayCardDetails {
+        let result = self.request_play(legal_plays);
+        self.timed_out = result == Err(BotFault::Timeout);
+        match result {
+            Ok(details) => details,
+            Err(fault) => {
+                self.faults.push(fault);
+                legal_plays[0].clone()
+            }
+        }
+    }
+
+    fn is_connected(&mut self) -> bool {
+        !self.faults.contains(&BotFault::Disconnected) && matches!(self.child.try_wait(), Ok(None))
+    }
+
+    fn timed_out(&mut self) -> bool {
+        self.timed_out
+    }
+}
+
+impl Drop for SubprocessAgent {
+    fn drop(&mut self) {
+
+        // Give the bot a short time to exit cleanly before killing it.
+        let _ = self.send("quit");
+        for _ in 0..10 {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,148 @@
+//! The editions of Love Letter that the engine is able to play.
+//!
+//! The classic edition uses a deck of sixteen cards and is played by two to four players. The
+//! Premium edition adds nine more kinds of card and doubles the size of the deck to thirty-two
+//! cards, allowing up to eight players to take part.
+//!
+//! Lovecraft Letter adds one insane version of each classic card to the classic deck, for two to
+//! six players. A player with an insane card among their discards is insane, and at the start of
+//! each of their turns must make an insanity check: they reveal a card from the top of the deck
+//! for each insane card they have discarded, and are eliminated if any of them is insane. The
+//! revealed cards join their discards, and the last card in the deck is never revealed. In
+//! return, an insane card played by a player who was already insane has a stronger effect, and a
+//! player who discards Cthulhu after two other insane cards wins the round outright.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::card::Card;
+use crate::deck::Deck;
+
+/// The rules under which a game is played, determining the cards in the deck and the number of
+/// players who can take part.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Ruleset {
+
+    /// The classic edition, with a deck of 16 cards for two to four players.
+    #[default]
+    Classic,
+
+    /// The Premium edition, with a deck of 32 cards for two to eight players.
+    Premium,
+
+    /// Lovecraft Letter, with a deck of 24 sane and insane cards for two to six players.
+    Lovecraft,
+}
+
+impl Ruleset {
+
+    /// Every ruleset.
+    pub const ALL: [Ruleset; 3] = [Ruleset::Classic, Ruleset::Premium, Ruleset::Lovecraft];
+
+    /// Get the number of players who can take part in a game under this ruleset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::ruleset::Ruleset;
+    /// assert!(Ruleset::Classic.players().contains(&4));
+    /// assert!(!Ruleset::Classic.players().contains(&5));
+    /// assert!(Ruleset::Premium.players().contains(&8));
+    /// assert!(!Ruleset::Lovecraft.players().contains(&7));
+    /// ```
+    pub fn players(self) -> RangeInclusive<usize> {
+        match self {
+            Self::Classic => 2..=4,
+            Self::Premium => 2..=8,
+            Self::Lovecraft => 2..=6,
+        }
+    }
+
+    /// Get every card in the deck used under this ruleset, with repeats, in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::card::Card;
+    /// # use love_letter::ruleset::Ruleset;
+    /// assert_eq!(Ruleset::Classic.cards().len(), 16);
+    /// assert_eq!(Ruleset::Premium.cards().len(), 32);
+    /// assert_eq!(Ruleset::Lovecraft.cards().len(), 24);
+    /// assert!(!Ruleset::Classic.cards().contains(&Card::Bishop));
+    /// ```
+    pub fn cards(self) -> Vec<Card> {
+        let mut cards = Deck::for_ruleset(self).cards().to_vec();
+        cards.sort();
+        cards
+    }
+
+    /// Returns whether the deck used under this ruleset includes the given card.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::card::Card;
+    /// # use love_letter::ruleset::Ruleset;
+    /// assert!(Ruleset::Classic.includes(Card::Princess));
+    /// assert!(!Ruleset::Classic.includes(Card::Jester));
+    /// assert!(Ruleset::Premium.includes(Card::Jester));
+    /// assert!(!Ruleset::Premium.includes(Card::Cthulhu));
+    /// assert!(Ruleset::Lovecraft.includes(Card::Cthulhu));
+    /// ```
+    pub fn includes(self, card: Card) -> bool {
+        match self {
+            Self::Classic => Card::CLASSIC.contains(&card),
+            Self::Premium => !Card::INSANE.contains(&card),
+            Self::Lovecraft => Card::CLASSIC.contains(&card) || Card::INSANE.contains(&card),
+        }
+    }
+}
+
+impl fmt::Display for Ruleset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Classic => write!(f, "classic"),
+            Self::Premium => write!(f, "premium"),
+            Self::Lovecraft => write!(f, "lovecraft"),
+        }
+    }
+}
+
+impl FromStr for Ruleset {
+    type Err = ParseRulesetError;
+
+    /// Parse a ruleset from its name, ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::ruleset::Ruleset;
+    /// assert_eq!("Premium".parse(), Ok(Ruleset::Premium));
+    /// assert_eq!("lovecraft".parse(), Ok(Ruleset::Lovecraft));
+    /// assert!("arkham".parse::<Ruleset>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.iter()
+            .find(|ruleset| ruleset.to_string().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| ParseRulesetError(s.to_string()))
+    }
+}
+
+/// An error returned when a string does not name a ruleset.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ParseRulesetError(String);
+
+impl fmt::Display for ParseRulesetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown ruleset: {}.", self.0)
+    }
+}
+
+impl Error for ParseRulesetError {}
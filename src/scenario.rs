@@ -0,0 +1,284 @@
+//! Scenario files, describing a position, a sequence of plays and what should happen as a result.
+//!
+//! Scenarios allow rules interactions to be checked without writing any Rust. Each scenario is a
+//! TOML or JSON file, recognised by its extension, which sets out a position in the middle of a
+//! round (as with a `GameBuilder`) and a list of steps. Each step is a play by one of the players,
+//! together with either the events that it should produce or the error with which it should be
+//! rejected.
+//!
+//! Plays are written in the same way as in the bot protocol, for example `Guard 2 Baron`, and so
+//! are events, for example `discard 1 Princess`. Errors are written as they are printed by the
+//! `Debug` implementation of `GameError`, for example `CannotTargetProtectedPlayer` or
+//! `CannotPlayWhileHoldingCountess(King)`. If a step gives neither events nor an error, it only
+//! checks that the play succeeds.
+//!
+//! ```toml
+//! description = "A Prince played on the Princess eliminates her holder"
+//! current_player = 0
+//! deck = ["Guard", "Priest", "Baron"]  # The top of the deck first
+//! burned_card = "Guard"
+//!
+//! [[players]]
+//! hand = ["Guard", "Prince"]
+//! discards = ["Guard"]
+//!
+//! [[players]]
+//! hand = ["Princess"]
+//! protected = false
+//! eliminated = false
+//!
+//! [[steps]]
+//! player = 0
+//! play = "Prince 1"
+//! events = ["play 0 Prince", "discard 1 Princess", "eliminate 1 discarded 0 Princess", "endturn 0",
+//!           "final 0 Guard 1 6", "decided laststanding", "gameover 0"]
+//! ```
+//!
+//! The positions in scenarios are validated in the same way as by `GameBuilder`, so every card in
+//! the deck must be accounted for. Scenarios use the classic ruleset unless they give another, for
+//! example `ruleset = "Premium"`, and may instead use a custom deck given under `custom_deck`, in
+//! the format described in the `custom` module. Custom cards are written as `Custom(0)` and so on.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::action::Action;
+use crate::builder::{BuildError, GameBuilder};
+use crate::card::Card;
+use crate::custom::CustomDeck;
+use crate::game::Game;
+use crate::protocol;
+use crate::ruleset::Ruleset;
+
+/// A reason why a scenario could not be loaded, or did not behave as expected.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ScenarioError {
+
+    /// The scenario file could not be read or parsed.
+    Load(String),
+
+    /// The position described by the scenario is invalid.
+    InvalidPosition(BuildError),
+
+    /// A step contains a play which could not be understood.
+    InvalidPlay { step: usize, play: String },
+
+    /// A step produced different events to those expected.
+    WrongEvents { step: usize, expected: Vec<String>, actual: Vec<String> },
+
+    /// A step failed when it should have succeeded, succeeded when it should have failed, or
+    /// failed with a different error to the one expected.
+    WrongError { step: usize, expected: Option<String>, actual: Option<String> },
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ScenarioError::*;
+        let describe = |error: &Option<String>| error.clone().unwrap_or_else(|| "no error".to_string());
+        match self {
+            Load(message) => write!(f, "Could not load scenario: {}", message),
+            InvalidPosition(err) => write!(f, "Invalid position: {}", err),
+            InvalidPlay { step, play } => write!(f, "Step {}: could not understand the play \"{}\".", step, play),
+            WrongEvents { step, expected, actual } => write!(f, "Step {}: expected events [{}] but got [{}].", step, expected.join(", "), actual.join(", ")),
+            WrongError { step, expected, actual } => write!(f, "Step {}: expected {} but got {}.", step, describe(expected), describe(actual)),
+        }
+    }
+}
+
+impl Error for ScenarioError {}
+
+/// A single player in the starting position of a scenario.
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScenarioPlayer {
+
+    /// The cards held by the player, with the card they have held longest first.
+    pub hand: Vec<Card>,
+
+    /// The cards discarded by the player, in the order they were discarded.
+    pub discards: Vec<Card>,
+
+    /// Whether the player is protected by the Handmaid.
+    pub protected: bool,
+
+    /// Whether the player has been eliminated.
+    pub eliminated: bool,
+}
+
+/// A play made during a scenario, and what should happen as a result.
+#[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioStep {
+
+    /// The player making the play.
+    pub player: usize,
+
+    /// The play, in the format used by the bot protocol.
+    pub play: String,
+
+    /// The events that the play should produce, in the format used by the bot protocol.
+    #[serde(default)]
+    pub events: Option<Vec<String>>,
+
+    /// The error with which the play should be rejected.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A scenario: a starting position and a sequence of plays.
+#[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+
+    /// A description of what the scenario checks.
+    #[serde(default)]
+    pub description: String,
+
+    /// The rules under which the scenario is played.
+    #[serde(default)]
+    pub ruleset: Ruleset,
+
+    /// The custom deck used in place of the ruleset's cards, if any.
+    #[serde(default)]
+    pub custom_deck: Option<CustomDeck>,
+
+    /// The players in the starting position.
+    pub players: Vec<ScenarioPlayer>,
+
+    /// The player whose turn it is in the starting position.
+    #[serde(default)]
+    pub current_player: usize,
+
+    /// The cards in the deck, with the top of the deck first.
+    #[serde(default)]
+    pub deck: Vec<Card>,
+
+    /// The burned card, if it has not yet been drawn.
+    #[serde(default)]
+    pub burned_card: Option<Card>,
+
+    /// The cards publicly removed from the game at the start of a two-player game.
+    #[serde(default)]
+    pub removed_cards: Vec<Card>,
+
+    /// The plays to make, in order.
+    #[serde(default)]
+    pub steps: Vec<ScenarioStep>,
+}
+
+impl Scenario {
+
+    /// Parse a scenario written in TOML.
+    pub fn from_toml(text: &str) -> Result<Self, ScenarioError> {
+        toml::from_str(text).map_err(|err| ScenarioError::Load(err.to_string()))
+    }
+
+    /// Parse a scenario written in JSON.
+    pub fn from_json(text: &str) -> Result<Self, ScenarioError> {
+        serde_json::from_str(text).map_err(|err| ScenarioError::Load(err.to_string()))
+    }
+
+    /// Load a scenario from a file, which is parsed as JSON if it has a `.json` extension and as
+    /// TOML otherwise.
+    pub fn load(path: &Path) -> Result<Self, ScenarioError> {
+        let text = fs::read_to_string(path).map_err(|err| ScenarioError::Load(err.to_string()))?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Self::from_json(&text),
+            _ => Self::from_toml(&text),
+        }
+    }
+
+    /// Create a game in the starting position of this scenario.
+    pub fn game(&self) -> Result<Game, ScenarioError> {
+        let mut builder = GameBuilder::new(self.players.len())
+            .ruleset(self.ruleset)
+            .deck(&self.deck)
+            .burned_card(self.burned_card)
+            .removed_cards(&self.removed_cards)
+            .current_player(self.current_player);
+        if let Some(deck) = &self.custom_deck {
+            builder = builder.custom_deck(deck.clone());
+        }
+        for (player_idx, player) in self.players.iter().enumerate() {
+            builder = builder.hand(player_idx, &player.hand).discards(player_idx, &player.discards);
+            if player.protected {
+                builder = builder.protected(player_idx);
+            }
+            if player.eliminated {
+                builder = builder.eliminated(player_idx);
+            }
+        }
+        builder.build().map_err(ScenarioError::InvalidPosition)
+    }
+
+    /// Play through the scenario, checking that every step has the expected result. Steps are
+    /// numbered from 1.
+    pub fn run(&self) -> Result<(), ScenarioError> {
+        let mut game = self.game()?;
+        for (step, expected) in self.steps.iter().enumerate().map(|(idx, expected)| (idx + 1, expected)) {
+            let details = protocol::parse_play(&expected.play)
+                .ok_or_else(|| ScenarioError::InvalidPlay { step, play: expected.play.clone() })?;
+
+            match game.perform_action(&Action::PlayCard { player_idx: expected.player, details }) {
+                Ok(events) => {
+                    if expected.error.is_some() {
+                        return Err(ScenarioError::WrongError { step, expected: expected.error.clone(), actual: None });
+                    }
+                    let actual = events.iter().map(protocol::format_event).collect::<Vec<_>>();
+                    if let Some(expected) = expected.events.as_ref().filter(|&expected| *expected != actual) {
+                        return Err(ScenarioError::WrongEvents { step, expected: expected.clone(), actual });
+                    }
+                }
+                Err(err) => {
+                    let actual = format!("{:?}", err);
+                    if expected.error.as_ref() != Some(&actual) {
+                        return Err(ScenarioError::WrongError { step, expected: expected.error.clone(), actual: Some(actual) });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of running the scenario in a particular file.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ScenarioResult {
+
+    /// The file containing the scenario.
+    pub path: PathBuf,
+
+    /// Whether the scenario loaded and behaved as expected.
+    pub result: Result<(), ScenarioError>,
+}
+
+/// Load and run every scenario in the given directory (but not its subdirectories) with a `.toml`
+/// or `.json` extension, returning the result of each in order of file name.
+///
+/// # Examples
+///
+/// ```
+/// # use std::path::Path;
+/// # use love_letter::scenario;
+/// for outcome in scenario::run_directory(Path::new("scenarios")).unwrap() {
+///     assert_eq!(outcome.result, Ok(()), "{}", outcome.path.display());
+/// }
+/// ```
+pub fn run_directory(dir: &Path) -> Result<Vec<ScenarioResult>, ScenarioError> {
+    let entries = fs::read_dir(dir).map_err(|err| ScenarioError::Load(err.to_string()))?;
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| matches!(path.extension().and_then(|extension| extension.to_str()), Some("toml") | Some("json")))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    Ok(paths.into_iter().map(|path| {
+        let result = Scenario::load(&path).and_then(|scenario| scenario.run());
+        ScenarioResult { path, result }
+    }).collect())
+}
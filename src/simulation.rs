@@ -0,0 +1,387 @@
+//! A harness for playing large numbers of games between agents and collecting statistics.
+//!
+//! Games are spread across several threads, each of which owns a random number generator seeded
+//! from the overall seed of the simulation, so that a simulation with the same configuration always
+//! produces the same results. The statistics from each thread are merged into a single
+//! `SimulationStats`, which can be rendered as a text report or as CSV.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::thread;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::agent::{self, AgentError, AgentFactory, PlayOptions};
+use crate::card::Card;
+use crate::event::{EliminationReason, Event};
+use crate::game::Game;
+use crate::ruleset::Ruleset;
+
+/// The parameters of a simulation.
+#[derive(Clone)]
+pub struct SimulationConfig {
+
+    /// The rules being played, under whose name the results are reported.
+    pub ruleset: Ruleset,
+
+    /// The agents taking part, one for each seat.
+    pub agents: Vec<AgentFactory>,
+
+    /// The number of games to play.
+    pub games: usize,
+
+    /// The number of threads to spread the games across.
+    pub threads: usize,
+
+    /// The seed from which all randomness in the simulation is derived.
+    pub seed: u64,
+
+    /// Whether to rotate the agents around the table from one game to the next, so that each
+    /// agent spends an equal amount of time in each seat.
+    pub rotate_seats: bool,
+
+    /// The settings for each game, such as whether disconnected agents forfeit.
+    pub options: PlayOptions,
+}
+
+impl SimulationConfig {
+
+    /// A configuration for the given agents with a single thread, no seat rotation, a seed of 0
+    /// and the default `PlayOptions`.
+    pub fn new(ruleset: Ruleset, agents: Vec<AgentFactory>, games: usize) -> Self {
+        Self {
+            ruleset,
+            agents,
+            games,
+            threads: 1,
+            seed: 0,
+            rotate_seats: false,
+            options: PlayOptions::default(),
+        }
+    }
+}
+
+/// A count of games played and games won. A game won jointly by several players counts as an
+/// equal share of a win for each of them.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Tally {
+
+    /// The number of games played.
+    pub games: u64,
+
+    /// The number of games won.
+    pub wins: f64,
+}
+
+impl Tally {
+
+    /// The proportion of games played that were won.
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 { 0.0 } else { self.wins / self.games as f64 }
+    }
+
+    /// Combine another `Tally` into this one.
+    fn merge(&mut self, other: &Tally) {
+        self.games += other.games;
+        self.wins += other.wins;
+    }
+}
+
+/// What an elimination is put down to in the statistics.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum EliminationCause {
+
+    /// A card: the one played to eliminate the player, a card such as the Princess which they
+    /// played themselves, or a card such as the Assassin whose effect eliminated them.
+    Card(Card),
+
+    /// An insanity check revealed an insane card.
+    InsanityCheck,
+
+    /// The player ran out of time to make their move.
+    Timeout,
+
+    /// The player forfeited the game.
+    Forfeit,
+}
+
+impl fmt::Display for EliminationCause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Card(card) => write!(f, "{:?}", card),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+/// Statistics gathered from the games played under a single ruleset.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct RulesetStats {
+
+    /// The number of games played.
+    pub games: u64,
+
+    /// The results for each seat at the table.
+    pub seats: Vec<Tally>,
+
+    /// The results for each agent, by name.
+    pub agents: BTreeMap<String, Tally>,
+
+    /// The number of games lasting each number of turns.
+    pub round_lengths: BTreeMap<usize, u64>,
+
+    /// The number of eliminations put down to each cause.
+    pub eliminations: BTreeMap<EliminationCause, u64>,
+
+    /// The number of times each card was played.
+    pub card_plays: BTreeMap<Card, u64>,
+}
+
+impl RulesetStats {
+
+    /// Record the outcome of a single game, given the names of the agents in each seat and every
+    /// event that occurred during the game.
+    fn record_game(&mut self, names: &[String], events: &[Event]) {
+        self.games += 1;
+        if self.seats.len() < names.len() {
+            self.seats.resize(names.len(), Tally::default());
+        }
+
+        let mut turns = 0;
+        let mut last_card_played = None;
+        for event in events {
+            match event {
+                Event::PlayCard { card, .. } => {
+                    turns += 1;
+                    last_card_played = Some(*card);
+                    *self.card_plays.entry(*card).or_default() += 1;
+                }
+                Event::EliminatePlayer { reason, .. } => {
+                    use EliminationReason::*;
+                    let cause = match *reason {
+                        Guessed { .. } | LostComparison { .. } | Discarded { .. } => last_card_played.map(EliminationCause::Card),
+                        Played { card } | CardEffect { card, .. } => Some(EliminationCause::Card(card)),
+                        InsanityCheck { .. } => Some(EliminationCause::InsanityCheck),
+                        Timeout => Some(EliminationCause::Timeout),
+                        Forfeit => Some(EliminationCause::Forfeit),
+                    };
+                    if let Some(cause) = cause {
+                        *self.eliminations.entry(cause).or_default() += 1;
+                    }
+                }
+                Event::GameOver { winner_indices } => {
+                    let share = 1.0 / winner_indices.len() as f64;
+                    for &idx in winner_indices {
+                        self.seats[idx].wins += share;
+                        self.agents.entry(names[idx].clone()).or_default().wins += share;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        *self.round_lengths.entry(turns).or_default() += 1;
+        for (seat, name) in names.iter().enumerate() {
+            self.seats[seat].games += 1;
+            self.agents.entry(name.clone()).or_default().games += 1;
+        }
+    }
+
+    /// Combine another set of statistics into this one.
+    fn merge(&mut self, other: &RulesetStats) {
+        self.games += other.games;
+        if self.seats.len() < other.seats.len() {
+            self.seats.resize(other.seats.len(), Tally::default());
+        }
+        for (seat, tally) in other.seats.iter().enumerate() {
+            self.seats[seat].merge(tally);
+        }
+        for (name, tally) in &other.agents {
+            self.agents.entry(name.clone()).or_default().merge(tally);
+        }
+        for (&length, &count) in &other.round_lengths {
+            *self.round_lengths.entry(length).or_default() += count;
+        }
+        for (&cause, &count) in &other.eliminations {
+            *self.eliminations.entry(cause).or_default() += count;
+        }
+        for (&card, &count) in &other.card_plays {
+            *self.card_plays.entry(card).or_default() += count;
+        }
+    }
+
+    /// The mean number of turns in a game.
+    pub fn mean_round_length(&self) -> f64 {
+        let total = self.round_lengths.iter().map(|(&length, &count)| length as u64 * count).sum::<u64>();
+        if self.games == 0 { 0.0 } else { total as f64 / self.games as f64 }
+    }
+}
+
+/// Statistics gathered from one or more simulations, grouped by ruleset.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SimulationStats {
+
+    /// The statistics for each ruleset, by label.
+    pub rulesets: BTreeMap<String, RulesetStats>,
+}
+
+impl SimulationStats {
+
+    /// Combine another set of statistics into this one.
+    pub fn merge(&mut self, other: &SimulationStats) {
+        for (ruleset, stats) in &other.rulesets {
+            self.rulesets.entry(ruleset.clone()).or_default().merge(stats);
+        }
+    }
+
+    /// Render these statistics as CSV, with one row per statistic.
+    ///
+    /// The columns are `ruleset`, `category`, `key`, `count`, `games` and `rate`. For win rates,
+    /// `count` is the (possibly fractional) number of wins; for everything else, `rate` is the
+    /// count as a proportion of the number of games played.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("ruleset,category,key,count,games,rate\n");
+        for (ruleset, stats) in &self.rulesets {
+            let mut row = |category: &str, key: &str, count: f64, games: u64| {
+                let rate = if games == 0 { 0.0 } else { count / games as f64 };
+                csv.push_str(&format!("{},{},{},{},{},{:.6}\n", ruleset, category, key, count, games, rate));
+            };
+            for (seat, tally) in stats.seats.iter().enumerate() {
+                row("seat", &seat.to_string(), tally.wins, tally.games);
+            }
+            for (name, tally) in &stats.agents {
+                row("agent", name, tally.wins, tally.games);
+            }
+            for (length, &count) in &stats.round_lengths {
+                row("round_length", &length.to_string(), count as f64, stats.games);
+            }
+            for (cause, &count) in &stats.eliminations {
+                row("elimination", &cause.to_string(), count as f64, stats.games);
+            }
+            for (card, &count) in &stats.card_plays {
+                row("card_play", &format!("{:?}", card), count as f64, stats.games);
+            }
+        }
+        csv
+    }
+}
+
+impl fmt::Display for SimulationStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (ruleset, stats) in &self.rulesets {
+            writeln!(f, "Ruleset: {} ({} games, {:.2} turns on average)", ruleset, stats.games, stats.mean_round_length())?;
+            writeln!(f, "  Win rate by seat:")?;
+            for (seat, tally) in stats.seats.iter().enumerate() {
+                writeln!(f, "    {:<16} {:>7.2}%", seat, 100.0 * tally.win_rate())?;
+            }
+            writeln!(f, "  Win rate by agent:")?;
+            for (name, tally) in &stats.agents {
+                writeln!(f, "    {:<16} {:>7.2}%", name, 100.0 * tally.win_rate())?;
+            }
+            writeln!(f, "  Eliminations per game by cause:")?;
+            for (cause, &count) in &stats.eliminations {
+                writeln!(f, "    {:<16} {:>7.3}", cause.to_string(), count as f64 / stats.games as f64)?;
+            }
+            writeln!(f, "  Plays per game by card:")?;
+            for (card, &count) in &stats.card_plays {
+                writeln!(f, "    {:<16} {:>7.3}", format!("{:?}", card), count as f64 / stats.games as f64)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run a simulation with the given configuration, returning the statistics gathered.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::agent;
+/// # use love_letter::ruleset::Ruleset;
+/// # use love_letter::simulation::{self, SimulationConfig};
+/// let random = agent::factory_by_name("random").unwrap();
+/// let lowest = agent::factory_by_name("lowest-card").unwrap();
+/// let mut config = SimulationConfig::new(Ruleset::Classic, vec![random.clone(), lowest, random], 100);
+/// config.threads = 4;
+/// config.rotate_seats = true;
+///
+/// let stats = simulation::run(&config).unwrap();
+/// assert_eq!(stats.rulesets["classic"].games, 100);
+/// assert_eq!(stats, simulation::run(&config).unwrap());
+/// ```
+///
+/// Eliminations which no card caused, such as forfeits, are counted separately.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use love_letter::action::PlayCardDetails;
+/// # use love_letter::agent::{self, Agent, AgentFactory, PlayOptions};
+/// # use love_letter::observation::Observation;
+/// # use love_letter::ruleset::Ruleset;
+/// # use love_letter::simulation::{self, EliminationCause, SimulationConfig};
+/// // An agent which has lost its connection before the game begins.
+/// struct Disconnected;
+///
+/// impl Agent for Disconnected {
+///     fn name(&self) -> &str {
+///         "disconnected"
+///     }
+///
+///     fn choose_play(&mut self, _: &Observation, legal_plays: &[PlayCardDetails]) -> PlayCardDetails {
+///         legal_plays[0].clone()
+///     }
+///
+///     fn is_connected(&mut self) -> bool {
+///         false
+///     }
+/// }
+///
+/// let disconnected: AgentFactory = Arc::new(|_| Ok(Box::new(Disconnected) as Box<dyn Agent>));
+/// let random = agent::factory_by_name("random").unwrap();
+/// let mut config = SimulationConfig::new(Ruleset::Classic, vec![disconnected, random], 10);
+/// config.options = PlayOptions { forfeit_on_disconnect: true, ..Default::default() };
+///
+/// let stats = simulation::run(&config).unwrap();
+/// let stats = &stats.rulesets["classic"];
+/// assert_eq!(stats.eliminations[&EliminationCause::Forfeit], 10);
+/// assert_eq!(stats.agents["random"].wins, 10.0);
+/// ```
+pub fn run(config: &SimulationConfig) -> Result<SimulationStats, AgentError> {
+    let threads = config.threads.max(1);
+    let handles = (0..threads).map(|thread_idx| {
+        let config = config.clone();
+        let games = (thread_idx..config.games).step_by(threads).collect::<Vec<_>>();
+        thread::spawn(move || run_games(&config, thread_idx, &games))
+    }).collect::<Vec<_>>();
+
+    let mut stats = RulesetStats::default();
+    for handle in handles {
+        stats.merge(&handle.join().expect("simulation thread panicked")?);
+    }
+
+    let mut result = SimulationStats::default();
+    result.rulesets.insert(config.ruleset.to_string(), stats);
+    Ok(result)
+}
+
+/// Play the given games on the current thread, returning the statistics gathered.
+fn run_games(config: &SimulationConfig, thread_idx: usize, games: &[usize]) -> Result<RulesetStats, AgentError> {
+    let mut rng = StdRng::seed_from_u64(config.seed ^ (thread_idx as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    let mut stats = RulesetStats::default();
+
+    for &game_idx in games {
+        let seats = config.agents.len();
+        let offset = if config.rotate_seats { game_idx % seats.max(1) } else { 0 };
+        let mut agents = (0..seats)
+            .map(|seat| (config.agents[(seat + offset) % seats])(rng.gen()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let names = agents.iter().map(|agent| agent.name().to_string()).collect::<Vec<_>>();
+
+        let mut game = Game::with_seed(rng.gen()).with_ruleset(config.ruleset);
+        let events = agent::play_game_with(&mut game, &mut agents, config.options)?;
+        stats.record_game(&names, &events);
+    }
+
+    Ok(stats)
+}
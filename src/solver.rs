@@ -0,0 +1,190 @@
+//! An exact solver for positions in which everything is known, including the order of the deck.
+//!
+//! Love Letter is a game of hidden information, so the solutions computed here are not the moves a
+//! real player should make. They are instead useful for analysis: debugging the decisions of bots,
+//! creating puzzles, and answering "could I have won that game?" after the fact.
+//!
+//! The value of a position is, for each player, their share of the win under optimal play - 1 for
+//! an outright win, `1 / n` for a tie between `n` players, and 0 otherwise. In two-player games
+//! this is computed by minimax with alpha-beta pruning. With more players, each player is assumed
+//! to maximise their own share (the max^n algorithm), with ties broken in favour of the earliest
+//! legal play.
+//!
+//! In games where players make their own choices (see `Game::with_choices`), each choice is a move
+//! like any other, made by the player who must choose.
+//!
+//! The size of the search grows very quickly with the number of cards left in the deck, so the
+//! solver is best suited to positions late in a round.
+
+use crate::action::{Action, PlayCardDetails};
+use crate::event::Event;
+use crate::game::{Game, GameError};
+
+/// The game-theoretic result of one legal play.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlayValue {
+
+    /// The play being evaluated.
+    pub details: PlayCardDetails,
+
+    /// Each player's share of the win if this play is made and everybody plays optimally
+    /// thereafter.
+    pub values: Vec<f64>,
+}
+
+/// Solve the given position, returning the value of every legal play for the current player, in
+/// the order given by `Game::legal_plays`. Returns an empty list if the game is not in progress,
+/// `GameError::ChoicePending` if a player must make a choice before anything can be played, or an
+/// error if the engine fails while searching.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::action::Action;
+/// # use love_letter::game::Game;
+/// # use love_letter::solver;
+/// let mut game = Game::with_seed(3);
+/// game.perform_action(&Action::StartGame { players: 2 }).unwrap();
+///
+/// // Play the lowest card available until the end of the round is in sight.
+/// while game.observation(0).deck_size > 4 {
+///     let player_idx = game.current_player().unwrap();
+///     let details = game.legal_plays(player_idx).into_iter().min_by_key(|d| d.card()).unwrap();
+///     game.perform_action(&Action::PlayCard { player_idx, details }).unwrap();
+/// }
+///
+/// if let Some(player_idx) = game.current_player() {
+///     let results = solver::solve(&game).unwrap();
+///     assert_eq!(results.len(), game.legal_plays(player_idx).len());
+///     for result in &results {
+///         assert!((result.values.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+///     }
+///
+///     // The value of the position is that of the best play.
+///     let best = results.iter().map(|result| result.values[player_idx]).fold(0.0, f64::max);
+///     assert_eq!(solver::value(&game).unwrap()[player_idx], best);
+/// }
+/// ```
+pub fn solve(game: &Game) -> Result<Vec<PlayValue>, GameError> {
+    if let Some(pending) = game.pending_choice() {
+        return Err(GameError::ChoicePending(pending.player_idx));
+    }
+    let player_idx = match game.current_player() {
+        Some(player_idx) => player_idx,
+        None => return Ok(Vec::new()),
+    };
+
+    game.legal_plays(player_idx).into_iter().map(|details| {
+        let values = action_value(game, &Action::PlayCard { player_idx, details: details.clone() }, 0.0, 1.0)?;
+        Ok(PlayValue { details, values })
+    }).collect()
+}
+
+/// The value of the given position under optimal play: each player's share of the win. If the game
+/// is not in progress, every player's value is 0. If a player must make a choice, the value is
+/// that of their best choice.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::action::{Action, PlayCardDetails};
+/// # use love_letter::builder::GameBuilder;
+/// # use love_letter::card::Card;
+/// # use love_letter::choice::Choice;
+/// # use love_letter::game::GameError;
+/// # use love_letter::ruleset::Ruleset;
+/// # use love_letter::solver;
+/// use Card::*;
+///
+/// // After playing the Cardinal, player 0 chooses which of the two swapped hands to look at.
+/// let mut game = GameBuilder::new(3)
+///     .ruleset(Ruleset::Premium)
+///     .hand(0, &[Cardinal, Guard])
+///     .hand(1, &[King])
+///     .hand(2, &[Baron])
+///     .deck(&[Princess, Guard])
+///     .removed_cards(&[Jester, Assassin, Guard, Guard, Guard, Guard, Guard, Guard, Priest, Priest, Cardinal,
+///         Baron, Baroness, Baroness, Handmaid, Handmaid, Sycophant, Sycophant, Prince, Prince, Count, Count,
+///         Constable, Countess, DowagerQueen, Bishop])
+///     .build()
+///     .unwrap()
+///     .with_choices();
+/// let details = PlayCardDetails::PlayCardinal { target_idx: Some(1), second_target_idx: Some(2) };
+/// game.perform_action(&Action::PlayCard { player_idx: 0, details }).unwrap();
+/// assert_eq!(solver::solve(&game), Err(GameError::ChoicePending(0)));
+///
+/// // Each choice leads to a position the solver can value, and the best of them is the value of
+/// // the position.
+/// let mut best = 0.0;
+/// for choice in game.legal_choices(0) {
+///     let mut child = game.clone();
+///     child.perform_action(&Action::MakeChoice { player_idx: 0, choice }).unwrap();
+///     best = f64::max(best, solver::value(&child).unwrap()[0]);
+/// }
+/// assert_eq!(solver::value(&game).unwrap()[0], best);
+/// ```
+pub fn value(game: &Game) -> Result<Vec<f64>, GameError> {
+    match moves(game) {
+        Some(_) => search(game, 0.0, 1.0),
+        None => Ok(vec![0.0; game.num_players()]),
+    }
+}
+
+/// Get the player due to move in the given position, and every action they could legally take:
+/// the choice they must make if one is pending, or otherwise every play available to the current
+/// player. Returns `None` if the game is not in progress.
+fn moves(game: &Game) -> Option<(usize, Vec<Action>)> {
+    if let Some(pending) = game.pending_choice() {
+        let player_idx = pending.player_idx;
+        let choices = game.legal_choices(player_idx).into_iter()
+            .map(|choice| Action::MakeChoice { player_idx, choice })
+            .collect();
+        return Some((player_idx, choices));
+    }
+
+    let player_idx = game.current_player()?;
+    let plays = game.legal_plays(player_idx).into_iter()
+        .map(|details| Action::PlayCard { player_idx, details })
+        .collect();
+    Some((player_idx, plays))
+}
+
+/// Search the position, which must be in progress. In two-player games, only values of player 0
+/// strictly between `alpha` and `beta` are exact; values outside that window are bounds.
+fn search(game: &Game, mut alpha: f64, mut beta: f64) -> Result<Vec<f64>, GameError> {
+    let (player_idx, actions) = moves(game).ok_or(GameError::InconsistentState)?;
+    let two_player = game.num_players() == 2;
+
+    let mut best: Option<Vec<f64>> = None;
+    for action in actions {
+        let values = action_value(game, &action, alpha, beta)?;
+        let improves = best.as_ref().map_or(true, |best| values[player_idx] > best[player_idx]);
+        let value = values[0];
+        if improves {
+            best = Some(values);
+        }
+
+        if two_player {
+            if player_idx == 0 { alpha = alpha.max(value); } else { beta = beta.min(value); }
+            if alpha >= beta {
+                break;
+            }
+        }
+    }
+
+    // A player due to move always has at least one legal play or choice.
+    best.ok_or(GameError::InconsistentState)
+}
+
+/// The value of taking the given action in the given position.
+fn action_value(game: &Game, action: &Action, alpha: f64, beta: f64) -> Result<Vec<f64>, GameError> {
+    let mut child = game.clone();
+    let events = child.perform_action(action)?;
+
+    match events.last() {
+        Some(Event::GameOver { winner_indices }) => Ok((0..game.num_players()).map(|idx| {
+            if winner_indices.contains(&idx) { 1.0 / winner_indices.len() as f64 } else { 0.0 }
+        }).collect()),
+        _ => search(&child, alpha, beta),
+    }
+}
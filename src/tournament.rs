@@ -0,0 +1,497 @@
+//! A tournament runner for comparing agents against one another.
+//!
+//! A tournament is made up of rounds, in each of which the entrants are split into tables of as
+//! many players as the ruleset allows, from two to four in the classic game. Each table plays a
+//! match of several games, with the entrants rotating around the table between games so that
+//! nobody benefits from always playing first. The entrants at a table
+//! are ranked by the number of games they won in the match, and every pair of entrants at the
+//! table is treated as a single rated game between the two of them.
+//!
+//! Two ratings are maintained for each entrant: a classic Elo rating and a Glicko-2 rating. Both
+//! are updated at the end of each round, using the ratings from the start of the round.
+
+use std::collections::HashSet;
+use std::f64::consts::PI;
+use std::fmt;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::agent::{self, AgentError, AgentFactory};
+use crate::event::Event;
+use crate::game::{Game, GameError};
+use crate::ruleset::Ruleset;
+
+/// The scale factor between Glicko and Glicko-2 ratings.
+const GLICKO2_SCALE: f64 = 173.7178;
+
+/// The default rating given to a new entrant.
+const INITIAL_RATING: f64 = 1500.0;
+
+/// The default Glicko rating deviation given to a new entrant.
+const INITIAL_DEVIATION: f64 = 350.0;
+
+/// The default Glicko-2 volatility given to a new entrant.
+const INITIAL_VOLATILITY: f64 = 0.06;
+
+/// The tolerance used when solving for a new volatility.
+const VOLATILITY_TOLERANCE: f64 = 0.000_001;
+
+/// An agent taking part in a tournament.
+#[derive(Clone)]
+pub struct Entrant {
+
+    /// The name under which the entrant's results are reported.
+    pub name: String,
+
+    /// A function creating a fresh instance of the entrant's agent.
+    pub factory: AgentFactory,
+}
+
+/// The way in which entrants are drawn into tables for each round.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TournamentFormat {
+
+    /// Every possible table of entrants plays exactly one match, in a single round.
+    RoundRobin,
+
+    /// The given number of rounds is played, with entrants on similar scores drawn together and
+    /// repeat meetings avoided where possible. If the entrants don't divide evenly into tables,
+    /// the leftovers form a smaller table, or sit the round out if there is only one of them.
+    Swiss { rounds: usize },
+}
+
+/// The parameters of a tournament.
+#[derive(Clone, Debug)]
+pub struct TournamentConfig {
+
+    /// How entrants are drawn into tables.
+    pub format: TournamentFormat,
+
+    /// The rules every game is played under.
+    pub ruleset: Ruleset,
+
+    /// The number of entrants at each table, which must be a number of players the ruleset
+    /// allows.
+    pub table_size: usize,
+
+    /// The number of games making up each match.
+    pub games_per_match: usize,
+
+    /// The seed from which all randomness in the tournament is derived.
+    pub seed: u64,
+
+    /// The K-factor used for Elo updates.
+    pub elo_k_factor: f64,
+
+    /// The Glicko-2 system constant, constraining how quickly volatility can change.
+    pub glicko_tau: f64,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        Self {
+            format: TournamentFormat::RoundRobin,
+            ruleset: Ruleset::Classic,
+            table_size: 2,
+            games_per_match: 12,
+            seed: 0,
+            elo_k_factor: 32.0,
+            glicko_tau: 0.5,
+        }
+    }
+}
+
+/// A Glicko-2 rating, expressed on the familiar Glicko scale.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Glicko2Rating {
+
+    /// The rating itself.
+    pub rating: f64,
+
+    /// The rating deviation, describing the uncertainty in the rating.
+    pub deviation: f64,
+
+    /// The volatility, describing how consistent the entrant's performances are.
+    pub volatility: f64,
+}
+
+impl Default for Glicko2Rating {
+    fn default() -> Self {
+        Self { rating: INITIAL_RATING, deviation: INITIAL_DEVIATION, volatility: INITIAL_VOLATILITY }
+    }
+}
+
+impl Glicko2Rating {
+
+    /// The 95% confidence interval for the true rating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::tournament::Glicko2Rating;
+    /// let (low, high) = Glicko2Rating::default().confidence_interval();
+    /// assert!(low < 1500.0 && 1500.0 < high);
+    /// ```
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        (self.rating - 1.96 * self.deviation, self.rating + 1.96 * self.deviation)
+    }
+
+    /// Compute the rating after a rating period containing the given results, each of which is
+    /// a pair of an opponent's rating at the start of the period and the score achieved against
+    /// them (1 for a win, 0.5 for a draw, 0 for a loss).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use love_letter::tournament::Glicko2Rating;
+    /// let rating = Glicko2Rating { rating: 1500.0, deviation: 200.0, volatility: 0.06 };
+    /// let opponent = |rating, deviation| Glicko2Rating { rating, deviation, volatility: 0.06 };
+    /// let results = [(opponent(1400.0, 30.0), 1.0), (opponent(1550.0, 100.0), 0.0), (opponent(1700.0, 300.0), 0.0)];
+    ///
+    /// let updated = rating.updated(&results, 0.5);
+    /// assert!((updated.rating - 1464.06).abs() < 0.01);
+    /// assert!((updated.deviation - 151.52).abs() < 0.01);
+    /// ```
+    pub fn updated(&self, results: &[(Glicko2Rating, f64)], tau: f64) -> Self {
+        let mu = (self.rating - INITIAL_RATING) / GLICKO2_SCALE;
+        let phi = self.deviation / GLICKO2_SCALE;
+        let sigma = self.volatility;
+
+        // With no games played, only the deviation changes.
+        if results.is_empty() {
+            let deviation = (phi * phi + sigma * sigma).sqrt() * GLICKO2_SCALE;
+            return Self { deviation: deviation.min(INITIAL_DEVIATION), ..*self };
+        }
+
+        let g = |phi: f64| 1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt();
+        let terms = results.iter().map(|(opponent, score)| {
+            let opponent_mu = (opponent.rating - INITIAL_RATING) / GLICKO2_SCALE;
+            let g = g(opponent.deviation / GLICKO2_SCALE);
+            let expected = 1.0 / (1.0 + (-g * (mu - opponent_mu)).exp());
+            (g, expected, *score)
+        }).collect::<Vec<_>>();
+
+        let v = 1.0 / terms.iter().map(|(g, e, _)| g * g * e * (1.0 - e)).sum::<f64>();
+        let improvement = terms.iter().map(|(g, e, s)| g * (s - e)).sum::<f64>();
+        let delta = v * improvement;
+
+        // Solve for the new volatility using the Illinois algorithm.
+        let a = (sigma * sigma).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            ex * (delta * delta - phi * phi - v - ex) / (2.0 * (phi * phi + v + ex).powi(2)) - (x - a) / (tau * tau)
+        };
+        let mut lower = a;
+        let mut upper = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * tau) < 0.0 {
+                k += 1.0;
+            }
+            a - k * tau
+        };
+        let (mut f_lower, mut f_upper) = (f(lower), f(upper));
+        while (upper - lower).abs() > VOLATILITY_TOLERANCE {
+            let c = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+            let f_c = f(c);
+            if f_c * f_upper <= 0.0 {
+                lower = upper;
+                f_lower = f_upper;
+            } else {
+                f_lower /= 2.0;
+            }
+            upper = c;
+            f_upper = f_c;
+        }
+        let volatility = (lower / 2.0).exp();
+
+        let phi_star = (phi * phi + volatility * volatility).sqrt();
+        let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let new_mu = mu + new_phi * new_phi * improvement;
+
+        Self {
+            rating: new_mu * GLICKO2_SCALE + INITIAL_RATING,
+            deviation: new_phi * GLICKO2_SCALE,
+            volatility,
+        }
+    }
+}
+
+/// The expected score of a player rated `rating` against one rated `opponent` under Elo.
+fn elo_expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/// The standing of a single entrant in a tournament.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Standing {
+
+    /// The entrant's name.
+    pub name: String,
+
+    /// The number of matches played.
+    pub matches: usize,
+
+    /// The number of games played.
+    pub games: usize,
+
+    /// The number of games won, with shared wins counting fractionally.
+    pub wins: f64,
+
+    /// The entrant's tournament score: the sum over all matches of the proportion of games won.
+    pub points: f64,
+
+    /// The entrant's Elo rating.
+    pub elo: f64,
+
+    /// The entrant's Glicko-2 rating.
+    pub glicko: Glicko2Rating,
+}
+
+impl Standing {
+
+    /// A fresh standing for a new entrant.
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            matches: 0,
+            games: 0,
+            wins: 0.0,
+            points: 0.0,
+            elo: INITIAL_RATING,
+            glicko: Glicko2Rating::default(),
+        }
+    }
+}
+
+/// The outcome of a single match.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MatchResult {
+
+    /// The round in which the match was played, starting from 0.
+    pub round: usize,
+
+    /// The entrants at the table, as indices into the list of entrants.
+    pub entrants: Vec<usize>,
+
+    /// The number of games won by each entrant at the table, in the same order.
+    pub wins: Vec<f64>,
+}
+
+/// The final outcome of a tournament.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TournamentResult {
+
+    /// The standings of each entrant, in the order the entrants were given.
+    pub standings: Vec<Standing>,
+
+    /// Every match played, in the order they were played.
+    pub matches: Vec<MatchResult>,
+}
+
+impl TournamentResult {
+
+    /// The indices of the entrants, ordered from highest to lowest Glicko-2 rating.
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking = (0..self.standings.len()).collect::<Vec<_>>();
+        ranking.sort_by(|&a, &b| self.standings[b].glicko.rating.total_cmp(&self.standings[a].glicko.rating));
+        ranking
+    }
+}
+
+impl fmt::Display for TournamentResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<4} {:<20} {:>7} {:>8} {:>8} {:>7} {:>7} {:>17}", "#", "Entrant", "Matches", "Points", "Win %", "Elo", "Glicko", "95% interval")?;
+        for (position, idx) in self.ranking().into_iter().enumerate() {
+            let standing = &self.standings[idx];
+            let (low, high) = standing.glicko.confidence_interval();
+            let win_rate = if standing.games == 0 { 0.0 } else { 100.0 * standing.wins / standing.games as f64 };
+            writeln!(f, "{:<4} {:<20} {:>7} {:>8.2} {:>7.2}% {:>7.0} {:>7.0} {:>8.0}-{:<8.0}",
+                position + 1, standing.name, standing.matches, standing.points, win_rate, standing.elo, standing.glicko.rating, low, high)?;
+        }
+        Ok(())
+    }
+}
+
+/// Run a tournament between the given entrants.
+///
+/// # Examples
+///
+/// ```
+/// # use love_letter::agent;
+/// # use love_letter::tournament::{self, Entrant, TournamentConfig, TournamentFormat};
+/// let entrants = ["random", "lowest-card", "random"].iter().enumerate().map(|(idx, name)| Entrant {
+///     name: format!("{}-{}", name, idx),
+///     factory: agent::factory_by_name(name).unwrap(),
+/// }).collect::<Vec<_>>();
+/// let config = TournamentConfig { format: TournamentFormat::Swiss { rounds: 3 }, ..Default::default() };
+///
+/// let result = tournament::run(&entrants, &config).unwrap();
+/// assert_eq!(result.matches.len(), 3);
+/// assert!(result.standings.iter().all(|standing| standing.glicko.deviation < 350.0));
+/// ```
+///
+/// Larger tables are allowed when the ruleset supports more players.
+///
+/// ```
+/// # use love_letter::agent::{self, AgentError};
+/// # use love_letter::game::GameError;
+/// # use love_letter::ruleset::Ruleset;
+/// # use love_letter::tournament::{self, Entrant, TournamentConfig};
+/// let entrants = (0..6).map(|idx| Entrant {
+///     name: format!("random-{}", idx),
+///     factory: agent::factory_by_name("random").unwrap(),
+/// }).collect::<Vec<_>>();
+/// let mut config = TournamentConfig { table_size: 6, games_per_match: 2, ..Default::default() };
+/// assert_eq!(tournament::run(&entrants, &config).unwrap_err(), AgentError::Game(GameError::InvalidNumberOfPlayers(6)));
+///
+/// config.ruleset = Ruleset::Premium;
+/// let result = tournament::run(&entrants, &config).unwrap();
+/// assert_eq!(result.matches.len(), 1);
+/// ```
+pub fn run(entrants: &[Entrant], config: &TournamentConfig) -> Result<TournamentResult, AgentError> {
+    if !config.ruleset.players().contains(&config.table_size) {
+        return Err(GameError::InvalidNumberOfPlayers(config.table_size).into());
+    }
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut standings = entrants.iter().map(|entrant| Standing::new(&entrant.name)).collect::<Vec<_>>();
+    let mut matches = Vec::new();
+    let mut previous_meetings = HashSet::new();
+
+    let rounds = match config.format {
+        TournamentFormat::RoundRobin => 1,
+        TournamentFormat::Swiss { rounds } => rounds,
+    };
+
+    for round in 0..rounds {
+        let tables = match config.format {
+            TournamentFormat::RoundRobin => round_robin_tables(entrants.len(), config.table_size),
+            TournamentFormat::Swiss { .. } => swiss_tables(&standings, config.table_size, &previous_meetings),
+        };
+
+        let mut round_results = Vec::new();
+        for table in tables {
+            for (position, &a) in table.iter().enumerate() {
+                for &b in &table[position + 1..] {
+                    previous_meetings.insert((a.min(b), a.max(b)));
+                }
+            }
+            let wins = play_match(entrants, &table, config.ruleset, config.games_per_match, &mut rng)?;
+            round_results.push(MatchResult { round, entrants: table, wins });
+        }
+
+        update_standings(&mut standings, &round_results, config);
+        matches.append(&mut round_results);
+    }
+
+    Ok(TournamentResult { standings, matches })
+}
+
+/// Every possible table of the given size drawn from the given number of entrants.
+fn round_robin_tables(entrants: usize, table_size: usize) -> Vec<Vec<usize>> {
+    let mut tables = Vec::new();
+    let mut table = Vec::with_capacity(table_size);
+
+    fn extend(start: usize, entrants: usize, table_size: usize, table: &mut Vec<usize>, tables: &mut Vec<Vec<usize>>) {
+        if table.len() == table_size {
+            tables.push(table.clone());
+            return;
+        }
+        for next in start..entrants {
+            table.push(next);
+            extend(next + 1, entrants, table_size, table, tables);
+            table.pop();
+        }
+    }
+
+    extend(0, entrants, table_size, &mut table, &mut tables);
+    tables
+}
+
+/// Draw the entrants into tables for a Swiss round, grouping entrants with similar scores and
+/// avoiding repeat meetings where possible.
+fn swiss_tables(standings: &[Standing], table_size: usize, previous_meetings: &HashSet<(usize, usize)>) -> Vec<Vec<usize>> {
+    let mut unassigned = (0..standings.len()).collect::<Vec<_>>();
+    unassigned.sort_by(|&a, &b| standings[b].points.total_cmp(&standings[a].points)
+        .then(standings[b].glicko.rating.total_cmp(&standings[a].glicko.rating)));
+
+    let have_met = |a: usize, b: usize| previous_meetings.contains(&(a.min(b), a.max(b)));
+
+    let mut tables = Vec::new();
+    while unassigned.len() >= 2 {
+        let mut table = vec![unassigned.remove(0)];
+        while table.len() < table_size && !unassigned.is_empty() {
+            let position = unassigned.iter()
+                .position(|&candidate| table.iter().all(|&seated| !have_met(seated, candidate)))
+                .unwrap_or(0);
+            table.push(unassigned.remove(position));
+        }
+        tables.push(table);
+    }
+
+    tables
+}
+
+/// Play a match between the given entrants, returning the number of games won by each of them.
+fn play_match(entrants: &[Entrant], table: &[usize], ruleset: Ruleset, games: usize, rng: &mut StdRng) -> Result<Vec<f64>, AgentError> {
+    let mut wins = vec![0.0; table.len()];
+
+    for game_idx in 0..games {
+
+        // Rotate the entrants around the table so that each spends equal time in each seat.
+        let seating = (0..table.len()).map(|seat| (seat + game_idx) % table.len()).collect::<Vec<_>>();
+        let mut agents = seating.iter().map(|&position| (entrants[table[position]].factory)(rng.gen())).collect::<Result<Vec<_>, _>>()?;
+
+        let events = agent::play_game(&mut Game::with_seed(rng.gen()).with_ruleset(ruleset), &mut agents)?;
+        if let Some(Event::GameOver { winner_indices }) = events.last() {
+            for &seat in winner_indices {
+                wins[seating[seat]] += 1.0 / winner_indices.len() as f64;
+            }
+        }
+    }
+
+    Ok(wins)
+}
+
+/// Update the standings and ratings of each entrant with the results of a round.
+fn update_standings(standings: &mut [Standing], results: &[MatchResult], config: &TournamentConfig) {
+    let before = standings.to_vec();
+    let mut glicko_results = vec![Vec::new(); standings.len()];
+    let mut elo_changes = vec![0.0; standings.len()];
+
+    for result in results {
+        let games = config.games_per_match.max(1) as f64;
+        let opponents = (result.entrants.len() - 1) as f64;
+
+        for (position, &idx) in result.entrants.iter().enumerate() {
+            let standing = &mut standings[idx];
+            standing.matches += 1;
+            standing.games += config.games_per_match;
+            standing.wins += result.wins[position];
+            standing.points += result.wins[position] / games;
+
+            // Treat the match as a single game against each other entrant at the table, won by
+            // whoever won more games in the match.
+            for (other_position, &other_idx) in result.entrants.iter().enumerate() {
+                if other_idx != idx {
+                    let score = match result.wins[position].partial_cmp(&result.wins[other_position]) {
+                        Some(std::cmp::Ordering::Greater) => 1.0,
+                        Some(std::cmp::Ordering::Less) => 0.0,
+                        _ => 0.5,
+                    };
+                    glicko_results[idx].push((before[other_idx].glicko, score));
+                    let expected = elo_expected_score(before[idx].elo, before[other_idx].elo);
+                    elo_changes[idx] += config.elo_k_factor * (score - expected) / opponents;
+                }
+            }
+        }
+    }
+
+    for (idx, standing) in standings.iter_mut().enumerate() {
+        standing.elo += elo_changes[idx];
+        standing.glicko = before[idx].glicko.updated(&glicko_results[idx], config.glicko_tau);
+    }
+}